@@ -0,0 +1,53 @@
+//! Throughput of parsing and serializing packets.
+//!
+//! Run with `cargo bench`.
+use std::hint::black_box;
+
+use coe::{AnalogueCOEValue, COEValue, Packet, Payload};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+/// The largest possible packet, with payloads of as many different units as fit.
+fn full_packet() -> Packet {
+    let payloads: Vec<Payload> = AnalogueCOEValue::UNIT_IDS
+        .iter()
+        .step_by(2)
+        .take(31)
+        .enumerate()
+        .map(|(index, unit_id)| {
+            // the 1st of march 2024, so that the Date unit is valid as well
+            let value = AnalogueCOEValue::try_from((unit_id, &[1, 3, 0xe8, 0x07][..]))
+                .expect("This is a valid value for every unit");
+            Payload::new(58, index as u8, COEValue::Analogue(value))
+        })
+        .collect();
+    Packet::try_from_payloads(&payloads).expect("These are at most 31 valid payloads")
+}
+
+fn parse(c: &mut Criterion) {
+    let mut bytes = [0_u8; 252];
+    let length = full_packet()
+        .try_serialize_into(&mut bytes)
+        .expect("A packet always fits into 252 bytes");
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(length as u64));
+    group.bench_function("packet", |b| {
+        b.iter(|| Packet::try_from(black_box(&bytes[..length])))
+    });
+    group.finish();
+}
+
+fn serialize(c: &mut Criterion) {
+    let packet = full_packet();
+    let mut bytes = [0_u8; 252];
+
+    let mut group = c.benchmark_group("serialize");
+    group.throughput(Throughput::Bytes(packet.wire_size() as u64));
+    group.bench_function("packet", |b| {
+        b.iter(|| black_box(&packet).try_serialize_into(black_box(&mut bytes)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, serialize);
+criterion_main!(benches);
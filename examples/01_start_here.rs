@@ -39,9 +39,12 @@ async fn listener() -> Result<(), ListenerError> {
     loop {
         let (length, sender) = socket.recv_from(&mut buf).await?;
         let mut packet = TryInto::<coe::Packet>::try_into(&buf[0..length])?;
-        for payload in packet.iter_mut() {
+        for mut payload in packet.iter_mut() {
             // update all payloads and let them point to another CAN-ID
-            *payload = Payload::new(23, payload.pdo_index(), payload.value());
+            let moved = Payload::new(23, payload.pdo_index(), payload.value());
+            payload
+                .try_set(moved)
+                .expect("The payload was valid before.");
         }
 
         // now forward the result back
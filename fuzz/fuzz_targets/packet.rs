@@ -0,0 +1,17 @@
+//! Parse arbitrary bytes as a [Packet] and check that serializing it yields the same bytes.
+#![no_main]
+
+use coe::Packet;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(packet) = Packet::try_from(data) {
+        let mut buf = [0_u8; 4 + 31 * 8];
+        let len = packet
+            .try_serialize_into(&mut buf)
+            .expect("A parsed packet always fits into the largest possible packet size");
+        assert_eq!(len, packet.wire_size());
+        assert_eq!(&buf[..len], data);
+        assert_eq!(Packet::try_from(&buf[..len]), Ok(packet));
+    }
+});
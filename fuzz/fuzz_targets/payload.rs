@@ -0,0 +1,20 @@
+//! Parse arbitrary bytes as a [Payload] and check that it survives a round-trip through a
+//! [Packet].
+#![no_main]
+
+use coe::{Packet, Payload};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(payload) = Payload::try_from(data) {
+        let mut packet = Packet::new();
+        packet
+            .try_push(payload)
+            .expect("A parsed payload can always be added to an empty packet");
+        let mut buf = [0_u8; 4 + 8];
+        packet
+            .try_serialize_into(&mut buf)
+            .expect("A single payload always fits into 12 bytes");
+        assert_eq!(&buf[4..], data);
+    }
+});
@@ -0,0 +1,14 @@
+//! Serialize arbitrary valid [Packet]s and check that parsing yields the same [Packet].
+#![no_main]
+
+use coe::Packet;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|packet: Packet| {
+    let mut buf = [0_u8; 4 + 31 * 8];
+    let len = packet
+        .try_serialize_into(&mut buf)
+        .expect("Every packet fits into the largest possible packet size");
+    assert_eq!(len, packet.wire_size());
+    assert_eq!(Packet::try_from(&buf[..len]), Ok(packet));
+});
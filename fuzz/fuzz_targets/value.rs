@@ -0,0 +1,19 @@
+//! Parse arbitrary bytes as unit ID and value of both [AnalogueCOEValue] and [DigitalCOEValue]
+//! and check the result against the unit ID.
+#![no_main]
+
+use coe::{AnalogueCOEValue, DigitalCOEValue};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((unit_id, value)) = data.split_first() else {
+        return;
+    };
+    if let Ok(analogue) = AnalogueCOEValue::try_from((unit_id, value)) {
+        assert_eq!(analogue.unit_id(), *unit_id);
+        assert_eq!(&analogue.raw().to_le_bytes()[..], value);
+    }
+    if let Ok(digital) = DigitalCOEValue::try_from((unit_id, value)) {
+        assert_eq!(digital.unit_id(), *unit_id);
+    }
+});
@@ -0,0 +1,205 @@
+/* C interface to coe-rs, a CoE v2 codec. Generated by cbindgen, do not edit. */
+
+#ifndef COE_H
+#define COE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of every function in this interface.
+ *
+ * The values 1-12 mirror the variants of `ParseCOEError`.
+ */
+typedef enum CoeStatus {
+  /**
+   * The call succeeded.
+   */
+  COE_STATUS_OK = 0,
+  /**
+   * The node is not in 1-62.
+   */
+  COE_STATUS_NODE_DISALLOWED = 1,
+  /**
+   * The PDO index is not in 0-63.
+   */
+  COE_STATUS_PDO_INDEX_DISALLOWED = 2,
+  /**
+   * The unit does not exist for the given format.
+   */
+  COE_STATUS_FORMAT_AND_UNIT_INCOMPATIBLE = 3,
+  /**
+   * The format is neither 0 (digital) nor 1 (analogue).
+   */
+  COE_STATUS_FORMAT_UNKNOWN = 4,
+  /**
+   * A value was not 4 bytes long.
+   */
+  COE_STATUS_VALUE_SIZE = 5,
+  /**
+   * A digital value was neither 0 nor 1.
+   */
+  COE_STATUS_VALUE_NOT_BOOL = 6,
+  /**
+   * The buffer is shorter then the 4 byte CoE header.
+   */
+  COE_STATUS_PACKET_BELOW_HEADER_LENGTH = 7,
+  /**
+   * The CoE version is not 2.0.
+   */
+  COE_STATUS_VERSION_NOT_IMPLEMENTED = 8,
+  /**
+   * Packet size and payload count in the header are inconsistent.
+   */
+  COE_STATUS_PACKET_LENGTH_INCONSISTENT = 9,
+  /**
+   * The buffer length differs from the packet size in the header.
+   */
+  COE_STATUS_PACKET_SIZE_CONFLICTS_WITH_HEADER = 10,
+  /**
+   * A payload was not 8 bytes long.
+   */
+  COE_STATUS_PAYLOAD_FRAME_LENGTH_INCORRECT = 11,
+  /**
+   * A date does not exist in the calendar.
+   */
+  COE_STATUS_DATE_INVALID = 12,
+  /**
+   * A pointer argument was NULL.
+   */
+  COE_STATUS_NULL_POINTER = 100,
+  /**
+   * The output buffer is too small.
+   */
+  COE_STATUS_BUFFER_TOO_SMALL = 101,
+  /**
+   * The packet already contains 31 payloads, or claims to contain more.
+   */
+  COE_STATUS_PACKET_FULL = 102,
+} CoeStatus;
+
+/**
+ * A payload in its on-wire representation.
+ */
+typedef struct CoePayload {
+  /**
+   * The CAN-ID of the virtual node (1-62).
+   */
+  uint8_t node;
+  /**
+   * The output index (0-63), without the offset of 1 shown in the GUI.
+   */
+  uint8_t pdo_index;
+  /**
+   * 0 for digital and 1 for analogue values.
+   */
+  uint8_t format;
+  /**
+   * The unit ID.
+   */
+  uint8_t unit_id;
+  /**
+   * The unscaled value. Digital values are 0 or 1.
+   */
+  int32_t raw;
+} CoePayload;
+
+/**
+ * A packet with up to 31 payloads.
+ */
+typedef struct CoePacket {
+  /**
+   * The major CoE version, always 2.
+   */
+  uint8_t version_major;
+  /**
+   * The minor CoE version, always 0.
+   */
+  uint8_t version_minor;
+  /**
+   * The number of used entries in `payloads`.
+   */
+  uint8_t length;
+  /**
+   * The payloads. Only the first `length` entries are used.
+   */
+  struct CoePayload payloads[31];
+} CoePacket;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Parse `length` bytes at `buf` into `out`.
+ *
+ * # Safety
+ *
+ * `buf` must point to `length` readable bytes and `out` to a writable [CoePacket].
+ */
+enum CoeStatus coe_packet_parse(const uint8_t *buf, size_t length, struct CoePacket *out);
+
+/**
+ * Initialize `out` as a packet without payloads.
+ *
+ * # Safety
+ *
+ * `out` must point to a writable [CoePacket].
+ */
+enum CoeStatus coe_packet_init(struct CoePacket *out);
+
+/**
+ * Build a payload from node, PDO index, unit ID and the unscaled value.
+ *
+ * The format is derived from the unit, because every unit is either analogue or digital.
+ *
+ * # Safety
+ *
+ * `out` must point to a writable [CoePayload].
+ */
+enum CoeStatus coe_payload_new(uint8_t node,
+                               uint8_t pdo_index,
+                               uint8_t unit_id,
+                               int32_t raw,
+                               struct CoePayload *out);
+
+/**
+ * Append a copy of `payload` to `packet`, after validating it.
+ *
+ * # Safety
+ *
+ * `packet` must point to a valid, writable [CoePacket] and `payload` to a readable [CoePayload].
+ */
+enum CoeStatus coe_packet_push(struct CoePacket *packet, const struct CoePayload *payload);
+
+/**
+ * Validate `packet` and serialize it into the `length` bytes at `buf`.
+ *
+ * On success, the number of bytes written (at most 252) is stored in `written`.
+ *
+ * # Safety
+ *
+ * `packet` must point to a readable [CoePacket], `buf` to `length` writable bytes and `written`
+ * to a writable `size_t`.
+ */
+enum CoeStatus coe_packet_serialize(const struct CoePacket *packet,
+                                    uint8_t *buf,
+                                    size_t length,
+                                    size_t *written);
+
+/**
+ * A static, NUL-terminated English description of `status`, a [CoeStatus].
+ *
+ * Takes the status as an integer, so that C callers may pass any value. Values that are not a
+ * [CoeStatus] get a generic description.
+ */
+const char *coe_status_message(int32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* COE_H */
@@ -0,0 +1,366 @@
+//! Python bindings for coe-rs.
+//!
+//! Every parse, validation and scaling step calls into the `coe` crate, so Python scripts build
+//! and decode exactly the bytes the Rust implementation does.
+//!
+//! Build and install the module into the current virtualenv with
+//! ```sh
+//! maturin develop --release
+//! ```
+
+use pyo3::{
+    create_exception,
+    exceptions::{PyIndexError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+
+use coe::{AnalogueCOEValue, COEValue, ConvertUnitError, DigitalCOEValue, Format, ParseCOEError};
+
+create_exception!(
+    coe,
+    ParseError,
+    PyValueError,
+    "Raised when bytes are not a valid CoE packet, payload or value."
+);
+create_exception!(
+    coe,
+    UnitError,
+    PyValueError,
+    "Raised when a value cannot be scaled or converted into a unit."
+);
+
+fn parse_error(e: ParseCOEError) -> PyErr {
+    ParseError::new_err(e.to_string())
+}
+
+fn unit_error(e: ConvertUnitError) -> PyErr {
+    UnitError::new_err(e.to_string())
+}
+
+/// The variant name of a value, e.g. `DegreeCentigrade_Tens`.
+fn variant_name<T: core::fmt::Debug>(value: &T) -> String {
+    let debug = format!("{value:?}");
+    match debug.split_once('(') {
+        Some((name, _)) => name.to_string(),
+        None => debug,
+    }
+}
+
+/// An analogue value, stored as its unit ID and raw on-wire integer.
+#[pyclass(module = "coe", frozen, eq, hash)]
+#[derive(Clone, PartialEq, Hash)]
+struct AnalogueValue(AnalogueCOEValue);
+
+#[pymethods]
+impl AnalogueValue {
+    /// Create a value from a unit ID and its raw on-wire integer.
+    #[staticmethod]
+    fn from_raw(unit_id: u8, raw: i32) -> PyResult<Self> {
+        AnalogueCOEValue::from_raw(unit_id, raw)
+            .map(Self)
+            .map_err(parse_error)
+    }
+
+    /// Create a value from a unit ID and the value as shown by TA-Hardware.
+    ///
+    /// The value is rounded half away from zero to the precision of the unit.
+    #[staticmethod]
+    fn from_scaled(unit_id: u8, value: f64) -> PyResult<Self> {
+        AnalogueCOEValue::from_scaled(unit_id, value)
+            .map(Self)
+            .map_err(unit_error)
+    }
+
+    #[getter]
+    fn unit_id(&self) -> u8 {
+        self.0.unit_id()
+    }
+
+    /// The raw on-wire integer.
+    #[getter]
+    fn raw(&self) -> i32 {
+        self.0.raw()
+    }
+
+    /// The value as shown by TA-Hardware, or `None` for dates.
+    #[getter]
+    fn scaled(&self) -> Option<f64> {
+        self.0.scaled()
+    }
+
+    /// The unit symbol, e.g. `°C`.
+    #[getter]
+    fn unit(&self) -> &'static str {
+        self.0.unit_symbol()
+    }
+
+    #[getter]
+    fn decimals(&self) -> u8 {
+        self.0.decimals()
+    }
+
+    /// The name of the unit in coe-rs, e.g. `DegreeCentigrade_Tens`.
+    #[getter]
+    fn name(&self) -> String {
+        variant_name(&self.0)
+    }
+
+    /// The physical dimension of the unit, or `None` if it cannot be converted.
+    #[getter]
+    fn dimension(&self) -> Option<String> {
+        self.0.dimension().map(|d| d.to_string())
+    }
+
+    /// Convert this value into the unit with ID `unit_id` of the same dimension.
+    fn convert_to(&self, unit_id: u8) -> PyResult<Self> {
+        self.0.try_convert_to(unit_id).map(Self).map_err(unit_error)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AnalogueValue.from_raw({}, {})",
+            self.0.unit_id(),
+            self.0.raw()
+        )
+    }
+
+    fn __str__(&self) -> String {
+        match self.0.scaled() {
+            Some(scaled) => format!(
+                "{scaled:.prec$} {}",
+                self.0.unit_symbol(),
+                prec = usize::from(self.0.decimals())
+            )
+            .trim_end()
+            .to_string(),
+            None => format!("{:?}", self.0),
+        }
+    }
+}
+
+/// A digital value, stored as its unit ID and a bool.
+#[pyclass(module = "coe", frozen, eq, hash)]
+#[derive(Clone, PartialEq, Hash)]
+struct DigitalValue(DigitalCOEValue);
+
+#[pymethods]
+impl DigitalValue {
+    #[new]
+    fn new(unit_id: u8, value: bool) -> PyResult<Self> {
+        DigitalCOEValue::try_from((&unit_id, &[u8::from(value), 0, 0, 0][..]))
+            .map(Self)
+            .map_err(parse_error)
+    }
+
+    #[getter]
+    fn unit_id(&self) -> u8 {
+        self.0.unit_id()
+    }
+
+    #[getter]
+    fn value(&self) -> bool {
+        self.0.value()
+    }
+
+    /// The name of the unit in coe-rs, e.g. `OnOff`.
+    #[getter]
+    fn name(&self) -> String {
+        variant_name(&self.0)
+    }
+
+    fn __bool__(&self) -> bool {
+        self.0.value()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DigitalValue({}, {})",
+            self.0.unit_id(),
+            if self.0.value() { "True" } else { "False" }
+        )
+    }
+}
+
+/// Either kind of value, as accepted by [Payload::new].
+#[derive(FromPyObject)]
+enum Value {
+    Analogue(AnalogueValue),
+    Digital(DigitalValue),
+}
+
+fn value_into_py(py: Python<'_>, value: COEValue) -> PyResult<Bound<'_, PyAny>> {
+    Ok(match value {
+        COEValue::Analogue(x) => Bound::new(py, AnalogueValue(x))?.into_any(),
+        COEValue::Digital(x) => Bound::new(py, DigitalValue(x))?.into_any(),
+    })
+}
+
+/// A single value sent to a node and pdo index.
+#[pyclass(module = "coe", frozen, eq, hash)]
+#[derive(Clone, PartialEq, Hash)]
+struct Payload(coe::Payload);
+
+impl Payload {
+    /// The 8 on-wire bytes of this payload.
+    fn bytes(&self) -> PyResult<Vec<u8>> {
+        let packet = coe::Packet::try_from_payloads(&[self.0])
+            .ok_or_else(|| ParseError::new_err("The payload contains an invalid date."))?;
+        Ok(packet.serialize_into_vec().split_off(4))
+    }
+}
+
+#[pymethods]
+impl Payload {
+    /// Create a payload, validating it exactly like a received one.
+    #[new]
+    fn new(node: u8, pdo_index: u8, value: Value) -> PyResult<Self> {
+        let value = match value {
+            Value::Analogue(x) => COEValue::Analogue(x.0),
+            Value::Digital(x) => COEValue::Digital(x.0),
+        };
+        let payload = Self(coe::Payload::new(node, pdo_index, value));
+        Self::from_bytes(&payload.bytes()?)
+    }
+
+    /// Parse the 8 on-wire bytes of a payload.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        coe::Payload::try_from(data).map(Self).map_err(parse_error)
+    }
+
+    /// Serialize into the 8 on-wire bytes.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.bytes()?))
+    }
+
+    #[getter]
+    fn node(&self) -> u8 {
+        self.0.node()
+    }
+
+    /// The on-wire pdo index, which the CMI GUI shows offset by one.
+    #[getter]
+    fn pdo_index(&self) -> u8 {
+        self.0.pdo_index()
+    }
+
+    /// `"analogue"` or `"digital"`.
+    #[getter]
+    fn format(&self) -> &'static str {
+        match self.0.format() {
+            Format::Analogue => "analogue",
+            Format::Digital => "digital",
+        }
+    }
+
+    #[getter]
+    fn unit_id(&self) -> u8 {
+        self.0.unit_id()
+    }
+
+    /// The contained [AnalogueValue] or [DigitalValue].
+    #[getter]
+    fn value<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        value_into_py(py, self.0.value())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "Payload({}, {}, {})",
+            self.0.node(),
+            self.0.pdo_index(),
+            value_into_py(py, self.0.value())?.repr()?
+        ))
+    }
+}
+
+/// A CoE packet of up to 31 payloads.
+#[pyclass(module = "coe", eq)]
+#[derive(PartialEq)]
+struct Packet(coe::Packet);
+
+#[pymethods]
+impl Packet {
+    #[new]
+    #[pyo3(signature = (payloads=None))]
+    fn new(payloads: Option<Vec<Payload>>) -> PyResult<Self> {
+        let payloads: Vec<coe::Payload> =
+            payloads.unwrap_or_default().iter().map(|p| p.0).collect();
+        coe::Packet::try_from_payloads(&payloads)
+            .map(Self)
+            .ok_or_else(|| PyValueError::new_err("A packet holds at most 31 payloads."))
+    }
+
+    /// Parse a packet as received on the wire.
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        coe::Packet::try_from(data).map(Self).map_err(parse_error)
+    }
+
+    /// Serialize into the on-wire bytes.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.0.serialize_into_vec())
+    }
+
+    /// Append a payload, failing if the packet is full.
+    fn append(&mut self, payload: Payload) -> PyResult<()> {
+        self.0
+            .try_push(payload.0)
+            .ok_or_else(|| PyValueError::new_err("A packet holds at most 31 payloads."))
+    }
+
+    #[getter]
+    fn payloads(&self) -> Vec<Payload> {
+        self.0.iter().map(|p| Payload(*p)).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<Payload> {
+        let len = self.0.len() as isize;
+        let index = if index < 0 { index + len } else { index };
+        if index < 0 || index >= len {
+            return Err(PyIndexError::new_err("packet index out of range"));
+        };
+        Ok(Payload(
+            *self
+                .0
+                .iter()
+                .nth(index as usize)
+                .expect("The index was checked above"),
+        ))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let payloads = self
+            .payloads()
+            .into_iter()
+            .map(|p| p.__repr__(py))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("Packet([{}])", payloads.join(", ")))
+    }
+}
+
+/// Split any number of payloads into as few packets as possible.
+#[pyfunction]
+fn packets_from_payloads(payloads: Vec<Payload>) -> PyResult<Vec<Packet>> {
+    let payloads: Vec<coe::Payload> = payloads.iter().map(|p| p.0).collect();
+    coe::try_packets_from_payloads(&payloads)
+        .map(|packets| packets.into_iter().map(Packet).collect())
+        .ok_or_else(|| ParseError::new_err("A payload contains an invalid date."))
+}
+
+#[pymodule(name = "coe")]
+fn coe_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<AnalogueValue>()?;
+    m.add_class::<DigitalValue>()?;
+    m.add_class::<Payload>()?;
+    m.add_class::<Packet>()?;
+    m.add_function(wrap_pyfunction!(packets_from_payloads, m)?)?;
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add("UnitError", m.py().get_type::<UnitError>())?;
+    Ok(())
+}
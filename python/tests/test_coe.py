@@ -0,0 +1,58 @@
+import pytest
+
+import coe
+
+
+def test_parse_and_serialize():
+    data = bytes.fromhex("02000c013a010101d7000000")
+    packet = coe.Packet.from_bytes(data)
+    assert len(packet) == 1
+    payload = packet[0]
+    assert (payload.node, payload.pdo_index, payload.format) == (58, 1, "analogue")
+    assert payload.value.scaled == 21.5
+    assert payload.value.unit == "°C"
+    assert str(payload.value) == "21.5 °C"
+    assert packet.to_bytes() == data
+    assert payload.to_bytes() == data[4:]
+    assert coe.Payload.from_bytes(data[4:]) == payload
+
+
+def test_build_packet():
+    temperature = coe.AnalogueValue.from_scaled(1, 21.54)
+    assert temperature == coe.AnalogueValue.from_raw(1, 215)
+    assert temperature.name == "DegreeCentigrade_Tens"
+    switch = coe.DigitalValue(43, True)
+    assert switch.value and switch.name == "OnOff"
+
+    packet = coe.Packet([coe.Payload(58, 1, temperature)])
+    packet.append(coe.Payload(58, 2, switch))
+    assert packet.payloads[1].value == switch
+    assert coe.Packet.from_bytes(packet.to_bytes()) == packet
+    assert len({packet[0], coe.Payload(58, 1, temperature)}) == 1
+
+
+def test_convert():
+    kelvin = coe.AnalogueValue.from_raw(7, 2947)
+    assert kelvin.dimension == "temperature"
+    assert kelvin.convert_to(1) == coe.AnalogueValue.from_raw(1, 216)
+    with pytest.raises(coe.UnitError):
+        kelvin.convert_to(23)
+
+
+def test_packets_from_payloads():
+    payloads = [coe.Payload(1, i % 64, coe.DigitalValue(43, False)) for i in range(40)]
+    assert [len(p) for p in coe.packets_from_payloads(payloads)] == [31, 9]
+    with pytest.raises(ValueError):
+        coe.Packet(payloads)
+
+
+def test_errors():
+    with pytest.raises(coe.ParseError):
+        coe.Packet.from_bytes(b"\x02\x00")
+    with pytest.raises(coe.ParseError):
+        coe.Payload(63, 0, coe.DigitalValue(43, True))
+    with pytest.raises(coe.ParseError):
+        coe.DigitalValue(1, True)
+    with pytest.raises(coe.UnitError):
+        coe.AnalogueValue.from_scaled(62, 1.0)
+    assert issubclass(coe.ParseError, ValueError)
@@ -0,0 +1,91 @@
+//! Implementations of [arbitrary::Arbitrary], generating only values that can be serialized and
+//! parsed again.
+//!
+//! Nodes are in 1-62, PDO indices in 0-63, [Packet]s contain at most 31 [Payload]s and every
+//! [AnalogueCOEValue::Date] exists in the calendar.
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    days_in_month,
+    generate::{ANALOGUE_UNIT_IDS, DATE_UNIT_ID, DIGITAL_UNIT_IDS},
+    AnalogueCOEValue, COEValue, DigitalCOEValue, Packet, Payload,
+};
+
+impl<'a> Arbitrary<'a> for AnalogueCOEValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let unit_id = *u.choose(ANALOGUE_UNIT_IDS)?;
+        if unit_id == DATE_UNIT_ID {
+            let year = u.arbitrary()?;
+            let month = u.int_in_range(1..=12)?;
+            let day =
+                u.int_in_range(1..=days_in_month(month, year).expect("month should be in 1-12"))?;
+            return Ok(AnalogueCOEValue::Date(day, month, year));
+        };
+        Ok(AnalogueCOEValue::from_raw(unit_id, u.arbitrary()?)
+            .expect("All units except Date accept every raw value"))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DigitalCOEValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let unit_id = u.choose(DIGITAL_UNIT_IDS)?;
+        let value = [u8::from(bool::arbitrary(u)?), 0, 0, 0];
+        Ok(DigitalCOEValue::try_from((unit_id, &value[..]))
+            .expect("Digital units accept 0 and 1 as value"))
+    }
+}
+
+impl<'a> Arbitrary<'a> for COEValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            Ok(COEValue::Analogue(u.arbitrary()?))
+        } else {
+            Ok(COEValue::Digital(u.arbitrary()?))
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for Payload {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Payload::new(
+            u.int_in_range(1..=62)?,
+            u.int_in_range(0..=63)?,
+            u.arbitrary()?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Packet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut packet = Packet::new();
+        for _ in 0..u.int_in_range(0..=31)? {
+            packet
+                .try_push(u.arbitrary()?)
+                .expect("A Packet can hold 31 valid Payloads");
+        }
+        Ok(packet)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn packet_roundtrip() {
+        // a simple LCG is enough to get varied input
+        let mut state: u32 = 12345;
+        let mut bytes = [0_u8; 1024];
+        for _ in 0..100 {
+            for b in bytes.iter_mut() {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                *b = (state >> 16) as u8;
+            }
+            let packet = Packet::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            let mut buf = [0_u8; 4 + 8 * 31];
+            let len = packet.try_serialize_into(&mut buf).unwrap();
+            assert_eq!(Packet::try_from(&buf[..len]), Ok(packet));
+        }
+    }
+}
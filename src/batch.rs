@@ -0,0 +1,458 @@
+//! Send and receive many [Packet]s with as few syscalls as possible.
+//!
+//! On Linux, [BatchSocket] uses `sendmmsg` and `recvmmsg`, which move a whole batch of datagrams
+//! with a single syscall. On other platforms (and on Linux kernels without these syscalls), it
+//! falls back to one `send_to` or `recv_from` per datagram.
+
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    vec,
+    vec::Vec,
+};
+
+use crate::{Packet, ParseCOEError};
+
+/// The size of every buffer in the pool.
+///
+/// The largest CoE packet is 252 bytes long. Receiving into larger buffers makes sure that longer
+/// datagrams are rejected by [Packet::try_from] instead of being truncated into a valid packet.
+const SLOT_SIZE: usize = 256;
+
+/// A [UdpSocket] which sends and receives [Packet]s in batches.
+///
+/// All packets of a batch are serialized into one contiguous buffer pool, which is allocated once
+/// and reused for every batch.
+/// ```rust
+/// # use coe::{BatchSocket, Packet};
+/// # use std::net::UdpSocket;
+/// let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+/// let address = receiver.local_addr().unwrap();
+/// let mut receiver = BatchSocket::new(receiver, 32);
+/// let mut sender = BatchSocket::new(UdpSocket::bind("127.0.0.1:0").unwrap(), 32);
+///
+/// let packets: Vec<_> = (0..3).map(|_| (Packet::new(), address)).collect();
+/// assert_eq!(sender.send_batch(&packets).unwrap(), 3);
+///
+/// let mut received = Vec::new();
+/// while received.len() < 3 {
+///     receiver.receive_batch(&mut received).unwrap();
+/// }
+/// assert!(received.iter().all(|(packet, _)| packet == &Ok(Packet::new())));
+/// ```
+pub struct BatchSocket {
+    socket: UdpSocket,
+    batch_size: usize,
+    buffers: Vec<u8>,
+}
+impl BatchSocket {
+    /// Use `socket`, which should already be bound, to send and receive up to `batch_size`
+    /// datagrams per syscall.
+    ///
+    /// # Panics
+    ///
+    /// When `batch_size` is 0.
+    pub fn new(socket: UdpSocket, batch_size: usize) -> BatchSocket {
+        assert!(batch_size > 0, "The batch size must be positive.");
+        BatchSocket {
+            socket,
+            batch_size,
+            buffers: vec![0; batch_size * SLOT_SIZE],
+        }
+    }
+
+    /// The underlying socket.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Return the underlying socket.
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+
+    /// The maximum number of datagrams sent or received per syscall.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Send every packet to its destination, in order.
+    ///
+    /// Returns the number of packets sent, which is the length of `packets`. When an error is
+    /// returned, some of the packets may already have been sent.
+    pub fn send_batch(&mut self, packets: &[(Packet, SocketAddr)]) -> io::Result<usize> {
+        for chunk in packets.chunks(self.batch_size) {
+            for ((packet, _), slot) in chunk.iter().zip(self.buffers.chunks_mut(SLOT_SIZE)) {
+                packet
+                    .try_serialize_into(slot)
+                    .expect("A packet always fits into a slot");
+            }
+            self.send_chunk(chunk)?;
+        }
+        Ok(packets.len())
+    }
+
+    /// Wait for at least one datagram, then receive up to [batch size](BatchSocket::batch_size)
+    /// datagrams that are available without waiting.
+    ///
+    /// Every datagram is parsed and appended to `out` with its sender. Datagrams which are not
+    /// valid CoE packets are appended as an error. Returns the number of datagrams received.
+    ///
+    /// Without `recvmmsg`, every call receives a single datagram.
+    pub fn receive_batch(
+        &mut self,
+        out: &mut Vec<(Result<Packet, ParseCOEError>, SocketAddr)>,
+    ) -> io::Result<usize> {
+        #[cfg(target_os = "linux")]
+        match linux::receive(&self.socket, &mut self.buffers, self.batch_size, out) {
+            Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {}
+            x => return x,
+        };
+        self.receive_single(out)
+    }
+
+    /// Send the first `chunk.len()` slots, which already contain the serialized packets.
+    fn send_chunk(&self, chunk: &[(Packet, SocketAddr)]) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        let first = match linux::send(&self.socket, &self.buffers, chunk) {
+            Ok(()) => return Ok(()),
+            // the kernel does not implement sendmmsg, so send the rest one by one
+            Err((sent, e)) if e.raw_os_error() == Some(libc::ENOSYS) => sent,
+            Err((_, e)) => return Err(e),
+        };
+        #[cfg(not(target_os = "linux"))]
+        let first = 0;
+        self.send_single(chunk, first)
+    }
+
+    /// Send the slots of `chunk`, starting at `first`, with one syscall per packet.
+    fn send_single(&self, chunk: &[(Packet, SocketAddr)], first: usize) -> io::Result<()> {
+        let slots = self.buffers.chunks(SLOT_SIZE);
+        for ((packet, destination), slot) in chunk.iter().zip(slots).skip(first) {
+            self.socket
+                .send_to(&slot[..packet.wire_size()], destination)?;
+        }
+        Ok(())
+    }
+
+    fn receive_single(
+        &mut self,
+        out: &mut Vec<(Result<Packet, ParseCOEError>, SocketAddr)>,
+    ) -> io::Result<usize> {
+        let slot = &mut self.buffers[..SLOT_SIZE];
+        let (length, source) = self.socket.recv_from(slot)?;
+        out.push((Packet::try_from(&slot[..length]), source));
+        Ok(1)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        io, mem,
+        net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket},
+        os::fd::AsRawFd,
+        ptr,
+        vec::Vec,
+    };
+
+    use super::SLOT_SIZE;
+    use crate::{Packet, ParseCOEError};
+
+    /// Convert `address` into its C representation and length.
+    fn to_sockaddr(address: &SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+        // SAFETY: all-zero bytes are a valid sockaddr_storage.
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let length = match address {
+            SocketAddr::V4(address) => {
+                let sin = libc::sockaddr_in {
+                    sin_family: libc::AF_INET as libc::sa_family_t,
+                    sin_port: address.port().to_be(),
+                    sin_addr: libc::in_addr {
+                        s_addr: u32::from(*address.ip()).to_be(),
+                    },
+                    sin_zero: [0; 8],
+                };
+                // SAFETY: sockaddr_storage is large enough and aligned for every socket address.
+                unsafe { ptr::write(ptr::addr_of_mut!(storage).cast(), sin) };
+                mem::size_of::<libc::sockaddr_in>()
+            }
+            SocketAddr::V6(address) => {
+                let sin6 = libc::sockaddr_in6 {
+                    sin6_family: libc::AF_INET6 as libc::sa_family_t,
+                    sin6_port: address.port().to_be(),
+                    sin6_flowinfo: address.flowinfo(),
+                    sin6_addr: libc::in6_addr {
+                        s6_addr: address.ip().octets(),
+                    },
+                    sin6_scope_id: address.scope_id(),
+                };
+                // SAFETY: sockaddr_storage is large enough and aligned for every socket address.
+                unsafe { ptr::write(ptr::addr_of_mut!(storage).cast(), sin6) };
+                mem::size_of::<libc::sockaddr_in6>()
+            }
+        };
+        (storage, length as libc::socklen_t)
+    }
+
+    /// Convert a socket address written by the kernel.
+    fn from_sockaddr(storage: &libc::sockaddr_storage) -> Option<SocketAddr> {
+        match i32::from(storage.ss_family) {
+            libc::AF_INET => {
+                // SAFETY: the family says that the kernel wrote a sockaddr_in.
+                let sin: &libc::sockaddr_in = unsafe { &*ptr::from_ref(storage).cast() };
+                Some(SocketAddr::V4(SocketAddrV4::new(
+                    Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)),
+                    u16::from_be(sin.sin_port),
+                )))
+            }
+            libc::AF_INET6 => {
+                // SAFETY: the family says that the kernel wrote a sockaddr_in6.
+                let sin6: &libc::sockaddr_in6 = unsafe { &*ptr::from_ref(storage).cast() };
+                Some(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::from(sin6.sin6_addr.s6_addr),
+                    u16::from_be(sin6.sin6_port),
+                    sin6.sin6_flowinfo,
+                    sin6.sin6_scope_id,
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// A message header for a single datagram in `slot` and the socket address in `address`.
+    fn header(
+        slot: &mut libc::iovec,
+        address: &mut libc::sockaddr_storage,
+        address_length: libc::socklen_t,
+    ) -> libc::mmsghdr {
+        // SAFETY: all-zero bytes are a valid mmsghdr. Some targets have private padding fields,
+        // so it cannot be built field by field.
+        let mut header: libc::mmsghdr = unsafe { mem::zeroed() };
+        header.msg_hdr.msg_name = ptr::from_mut(address).cast();
+        header.msg_hdr.msg_namelen = address_length;
+        header.msg_hdr.msg_iov = slot;
+        header.msg_hdr.msg_iovlen = 1;
+        header
+    }
+
+    /// Send the first `chunk.len()` slots of `buffers` to the destinations in `chunk`.
+    ///
+    /// On error, returns the number of datagrams that were sent before.
+    pub(super) fn send(
+        socket: &UdpSocket,
+        buffers: &[u8],
+        chunk: &[(Packet, SocketAddr)],
+    ) -> Result<(), (usize, io::Error)> {
+        let mut addresses: Vec<_> = chunk.iter().map(|(_, x)| to_sockaddr(x)).collect();
+        let mut slots: Vec<libc::iovec> = chunk
+            .iter()
+            .zip(buffers.chunks(SLOT_SIZE))
+            .map(|((packet, _), slot)| libc::iovec {
+                // the kernel only reads from the slots when sending
+                iov_base: slot.as_ptr().cast_mut().cast(),
+                iov_len: packet.wire_size(),
+            })
+            .collect();
+        let mut headers: Vec<libc::mmsghdr> = slots
+            .iter_mut()
+            .zip(addresses.iter_mut())
+            .map(|(slot, (address, length))| header(slot, address, *length))
+            .collect();
+
+        let mut sent = 0;
+        while sent < headers.len() {
+            // SAFETY: every header points to an iovec and a socket address, and every iovec
+            // points into buffers, all of which outlive the call.
+            let result = unsafe {
+                libc::sendmmsg(
+                    socket.as_raw_fd(),
+                    headers[sent..].as_mut_ptr(),
+                    (headers.len() - sent) as libc::c_uint,
+                    0,
+                )
+            };
+            if result < 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err((sent, e));
+                };
+            } else {
+                sent += result as usize;
+            };
+        }
+        Ok(())
+    }
+
+    /// Receive up to `batch_size` datagrams into the slots of `buffers`, waiting for the first one.
+    pub(super) fn receive(
+        socket: &UdpSocket,
+        buffers: &mut [u8],
+        batch_size: usize,
+        out: &mut Vec<(Result<Packet, ParseCOEError>, SocketAddr)>,
+    ) -> io::Result<usize> {
+        // SAFETY: all-zero bytes are a valid sockaddr_storage.
+        let empty: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut addresses = vec![empty; batch_size];
+        let mut slots: Vec<libc::iovec> = buffers
+            .chunks_mut(SLOT_SIZE)
+            .map(|slot| libc::iovec {
+                iov_base: slot.as_mut_ptr().cast(),
+                iov_len: slot.len(),
+            })
+            .collect();
+        let mut headers: Vec<libc::mmsghdr> = slots
+            .iter_mut()
+            .zip(addresses.iter_mut())
+            .map(|(slot, address)| {
+                header(
+                    slot,
+                    address,
+                    mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
+                )
+            })
+            .collect();
+
+        let received = loop {
+            // SAFETY: every header points to an iovec and a socket address, and every iovec
+            // points into buffers, all of which outlive the call.
+            let result = unsafe {
+                libc::recvmmsg(
+                    socket.as_raw_fd(),
+                    headers.as_mut_ptr(),
+                    headers.len() as libc::c_uint,
+                    libc::MSG_WAITFORONE,
+                    ptr::null_mut(),
+                )
+            };
+            if result >= 0 {
+                break result as usize;
+            };
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            };
+        };
+
+        for ((header, address), slot) in headers
+            .iter()
+            .zip(addresses.iter())
+            .zip(buffers.chunks(SLOT_SIZE))
+            .take(received)
+        {
+            // a UDP socket only receives from IPv4 and IPv6 addresses
+            if let Some(source) = from_sockaddr(address) {
+                let length = header.msg_len as usize;
+                out.push((Packet::try_from(&slot[..length]), source));
+            };
+        }
+        Ok(received)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, Payload};
+
+    fn packet(pdo_index: u8) -> Packet {
+        Packet::try_from_payloads(&[Payload::new(
+            58,
+            pdo_index,
+            COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+        )])
+        .unwrap()
+    }
+
+    fn receive_all(
+        socket: &mut BatchSocket,
+        count: usize,
+    ) -> Vec<(Result<Packet, ParseCOEError>, SocketAddr)> {
+        let mut received = Vec::new();
+        while received.len() < count {
+            socket.receive_batch(&mut received).unwrap();
+        }
+        received
+    }
+
+    #[test]
+    fn send_and_receive() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = receiver.local_addr().unwrap();
+        let mut receiver = BatchSocket::new(receiver, 8);
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let source = sender.local_addr().unwrap();
+        let mut sender = BatchSocket::new(sender, 8);
+
+        // more packets than fit into one batch
+        let packets: Vec<_> = (0..20).map(|i| (packet(i), address)).collect();
+        assert_eq!(sender.send_batch(&packets).unwrap(), 20);
+        let received = receive_all(&mut receiver, 20);
+        assert_eq!(received.len(), 20);
+        for ((received, sender), (sent, _)) in received.into_iter().zip(packets) {
+            assert_eq!(received, Ok(sent));
+            assert_eq!(sender, source);
+        }
+
+        // datagrams longer then any CoE packet are not truncated into valid ones
+        let full: Vec<_> = (0..31)
+            .map(|i| {
+                Payload::new(
+                    58,
+                    i,
+                    COEValue::Analogue(AnalogueCOEValue::Dimensionless(0)),
+                )
+            })
+            .collect();
+        let mut long = Packet::try_from_payloads(&full)
+            .unwrap()
+            .serialize_into_vec();
+        long.resize(300, 0);
+        sender.socket().send_to(&long, address).unwrap();
+        sender.socket().send_to(&[2, 0], address).unwrap();
+        let received = receive_all(&mut receiver, 2);
+        assert_eq!(
+            received[0].0,
+            Err(ParseCOEError::PacketSizeConflictsWithHeader(252, SLOT_SIZE))
+        );
+        assert_eq!(received[1].0, Err(ParseCOEError::PacketBelowHeaderLength));
+    }
+
+    #[test]
+    fn fallback() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = receiver.local_addr().unwrap();
+        let mut receiver = BatchSocket::new(receiver, 4);
+        let mut sender = BatchSocket::new(UdpSocket::bind("127.0.0.1:0").unwrap(), 4);
+
+        let packets: Vec<_> = (0..3).map(|i| (packet(i), address)).collect();
+        for ((packet, _), slot) in packets.iter().zip(sender.buffers.chunks_mut(SLOT_SIZE)) {
+            packet.try_serialize_into(slot).unwrap();
+        }
+        sender.send_single(&packets, 0).unwrap();
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            assert_eq!(receiver.receive_single(&mut received).unwrap(), 1);
+        }
+        let received: Vec<_> = received.into_iter().map(|(x, _)| x.unwrap()).collect();
+        assert_eq!(
+            received,
+            packets.into_iter().map(|(x, _)| x).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ipv6() {
+        let Ok(receiver) = UdpSocket::bind("[::1]:0") else {
+            // no IPv6 on this host
+            return;
+        };
+        let address = receiver.local_addr().unwrap();
+        let mut receiver = BatchSocket::new(receiver, 2);
+        let sender = UdpSocket::bind("[::1]:0").unwrap();
+        let source = sender.local_addr().unwrap();
+        let mut sender = BatchSocket::new(sender, 2);
+        sender.send_batch(&[(packet(1), address)]).unwrap();
+        assert_eq!(receive_all(&mut receiver, 1), [(Ok(packet(1)), source)]);
+    }
+}
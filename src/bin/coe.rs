@@ -0,0 +1,142 @@
+//! Command line tools for CoE traffic.
+//!
+//! ```text
+//! coe record <listen-address> <file> [--count <n>]
+//! coe replay <file> <target-address> [--speed <factor>] [--bind <address>]
+//! ```
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+    net::{SocketAddr, UdpSocket},
+    process::ExitCode,
+};
+
+use coe::{record_capture, replay_capture, CaptureReader, CaptureWriter};
+
+const USAGE: &str = "Usage:
+    coe record <listen-address> <file> [--count <n>]
+        Record all datagrams received on <listen-address> into <file>.
+    coe replay <file> <target-address> [--speed <factor>] [--bind <address>]
+        Send the datagrams in <file> to <target-address> with their original timing.
+        --speed divides all delays, e.g. 10 replays ten times as fast and inf without delay.";
+
+/// The arguments of a subcommand: positional arguments and `--name value` options.
+struct Arguments {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+impl Arguments {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Arguments, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("--{name} requires a value"))?;
+                options.push((name.to_owned(), value));
+            } else {
+                positional.push(arg);
+            }
+        }
+        Ok(Arguments {
+            positional,
+            options,
+        })
+    }
+
+    /// Take exactly `N` positional arguments.
+    fn positional<const N: usize>(&self) -> Result<[&str; N], String> {
+        if self.positional.len() != N {
+            return Err(format!(
+                "expected {N} arguments, got {}",
+                self.positional.len()
+            ));
+        };
+        Ok(core::array::from_fn(|i| self.positional[i].as_str()))
+    }
+
+    /// Parse the option `name`, accepting only the names in `known`.
+    fn option<T>(&self, name: &str, known: &[&str]) -> Result<Option<T>, String>
+    where
+        T: core::str::FromStr,
+        T::Err: core::fmt::Display,
+    {
+        if let Some((unknown, _)) = self.options.iter().find(|(n, _)| !known.contains(&&n[..])) {
+            return Err(format!("unknown option --{unknown}"));
+        };
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| {
+                value
+                    .parse()
+                    .map_err(|e| format!("invalid value for --{name}: {e}"))
+            })
+            .transpose()
+    }
+}
+
+fn record(args: Arguments) -> Result<(), Box<dyn Error>> {
+    let [listen, path] = args.positional()?;
+    let count: Option<usize> = args.option("count", &["count"])?;
+    let socket = UdpSocket::bind(listen)?;
+    let mut writer = CaptureWriter::new(BufWriter::new(File::create(path)?))?;
+    // the bound address is recorded with every datagram, not the address it was sent to
+    eprintln!(
+        "Recording datagrams on {} into {path}.",
+        socket.local_addr()?
+    );
+    let recorded = record_capture(&socket, &mut writer, count)?;
+    eprintln!("Recorded {recorded} datagrams.");
+    Ok(())
+}
+
+fn replay(args: Arguments) -> Result<(), Box<dyn Error>> {
+    let known = ["speed", "bind"];
+    let [path, target] = args.positional()?;
+    let target: SocketAddr = target.parse()?;
+    let speed: f64 = args.option("speed", &known)?.unwrap_or(1.0);
+    if speed.is_nan() || speed <= 0.0 {
+        return Err("--speed must be positive".into());
+    };
+    let bind: SocketAddr = match args.option("bind", &known)? {
+        Some(x) => x,
+        None if target.is_ipv4() => "0.0.0.0:0".parse()?,
+        None => "[::]:0".parse()?,
+    };
+    let socket = UdpSocket::bind(bind)?;
+    let reader = CaptureReader::new(BufReader::new(File::open(path)?))?;
+    let sent = replay_capture(reader, &socket, target, speed)?;
+    eprintln!("Replayed {sent} datagrams to {target}.");
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let res = match Arguments::parse(args) {
+        Err(e) => Err(e.into()),
+        Ok(args) => match command.as_deref() {
+            Some("record") => record(args),
+            Some("replay") => replay(args),
+            Some("help" | "--help" | "-h") => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
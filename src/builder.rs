@@ -0,0 +1,151 @@
+//! Build packets in const context.
+
+use crate::{Packet, Payload};
+
+/// Collects up to 31 [Payload]s and serializes them, entirely in const context.
+///
+/// Use this for fixed packets, which can then be stored in flash without any work at runtime:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, DigitalCOEValue, Packet, PacketBuilder, Payload};
+/// const CONFIG: PacketBuilder = PacketBuilder::new()
+///     .push(Payload::new(58, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))))
+///     .push(Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215))));
+/// static FRAME: [u8; CONFIG.wire_size()] = CONFIG.serialize();
+///
+/// let packet = Packet::try_from(&FRAME[..]).unwrap();
+/// assert_eq!(packet, CONFIG.build());
+/// ```
+///
+/// Mistakes are compile errors in const items, e.g. pushing more then 31 payloads, a node outside
+/// of 1-62, a PDO index outside of 0-63 or an invalid
+/// [AnalogueCOEValue::Date](crate::AnalogueCOEValue), or serializing into an array of the wrong
+/// size:
+/// ```compile_fail
+/// # use coe::{COEValue, DigitalCOEValue, PacketBuilder, Payload};
+/// static FRAME: [u8; 4] = PacketBuilder::new()
+///     .push(Payload::new(58, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))))
+///     .serialize();
+/// ```
+/// ```compile_fail
+/// # use coe::{COEValue, DigitalCOEValue, PacketBuilder, Payload};
+/// const CONFIG: PacketBuilder =
+///     PacketBuilder::new().push(Payload::new(63, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))));
+/// ```
+/// ```compile_fail
+/// # use coe::{COEValue, DigitalCOEValue, PacketBuilder, Payload};
+/// const CONFIG: PacketBuilder =
+///     PacketBuilder::new().push(Payload::new(58, 64, COEValue::Digital(DigitalCOEValue::OnOff(true))));
+/// ```
+/// ```compile_fail
+/// # use coe::{AnalogueCOEValue, COEValue, PacketBuilder, Payload};
+/// const CONFIG: PacketBuilder =
+///     PacketBuilder::new().push(Payload::new(58, 0, COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2024))));
+/// ```
+#[derive(Hash, Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PacketBuilder {
+    payloads: [Payload; 31],
+    length: u8,
+}
+impl Default for PacketBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl PacketBuilder {
+    /// Create a builder without payloads.
+    pub const fn new() -> PacketBuilder {
+        PacketBuilder {
+            payloads: [Payload::DEFAULT; 31],
+            length: 0,
+        }
+    }
+
+    /// Append a [Payload].
+    ///
+    /// Fails if the builder already contains 31 payloads, the node is not in 1-62, the PDO index is
+    /// not in 0-63 or the payload contains an invalid
+    /// [AnalogueCOEValue::Date](crate::AnalogueCOEValue).
+    pub const fn try_push(mut self, payload: Payload) -> Option<PacketBuilder> {
+        if self.length == 31 {
+            return None;
+        };
+        if payload.node() == 0 || payload.node() > 62 || payload.pdo_index() > 63 {
+            return None;
+        };
+        if payload.check().is_err() {
+            return None;
+        };
+        self.payloads[self.length as usize] = payload;
+        self.length += 1;
+        Some(self)
+    }
+
+    /// Append a [Payload].
+    ///
+    /// # Panics
+    ///
+    /// When [PacketBuilder::try_push] fails. In const context, this is a compile error.
+    pub const fn push(self, payload: Payload) -> PacketBuilder {
+        match self.try_push(payload) {
+            Some(builder) => builder,
+            None => panic!("a packet holds at most 31 valid payloads"),
+        }
+    }
+
+    /// The number of payloads in this builder.
+    pub const fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    /// Returns whether there are any payloads in this builder.
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The size of the serialized packet in bytes, i.e. the `N` for [PacketBuilder::serialize].
+    pub const fn wire_size(&self) -> usize {
+        4 + self.len() * 8
+    }
+
+    /// Serialize the packet into an array of exactly its [wire size](PacketBuilder::wire_size).
+    ///
+    /// # Panics
+    ///
+    /// When `N` is not the wire size. In const context, this is a compile error.
+    pub const fn serialize<const N: usize>(&self) -> [u8; N] {
+        assert!(
+            N == self.wire_size(),
+            "N must be the wire size of the packet"
+        );
+        let mut buf = [0_u8; N];
+        // the HEADER
+        buf[0] = 2;
+        buf[1] = 0;
+        buf[2] = 4 + self.length * 8;
+        buf[3] = self.length;
+        // the PAYLOADS
+        let mut index = 0;
+        while index < self.len() {
+            let bytes = self.payloads[index].wire_bytes();
+            let mut byte = 0;
+            while byte < 8 {
+                buf[4 + index * 8 + byte] = bytes[byte];
+                byte += 1;
+            }
+            index += 1;
+        }
+        buf
+    }
+
+    /// Create a [Packet] with the payloads of this builder.
+    pub fn build(&self) -> Packet {
+        Packet::try_from_payloads(&self.payloads[..self.len()])
+            .expect("The builder contains at most 31 payloads with valid dates")
+    }
+}
+impl From<PacketBuilder> for Packet {
+    fn from(value: PacketBuilder) -> Self {
+        value.build()
+    }
+}
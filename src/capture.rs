@@ -0,0 +1,493 @@
+//! Record raw CoE datagrams into a capture file and replay them later.
+//!
+//! A capture file starts with an 8 byte header: the magic `COECAP` followed by the format version
+//! (currently 1) and a reserved zero byte. The header is followed by any number of records, each
+//! consisting of (all integers little-endian, like CoE itself):
+//! 1. the time the datagram was received, in microseconds since the unix epoch, as u64
+//! 2. the source address
+//! 3. the local address the datagram was received on, see [CaptureRecord::local_address]
+//! 4. the length of the datagram, as u16
+//! 5. the datagram itself
+//!
+//! An address is its family (4 or 6) as u8, followed by the 4 or 16 bytes of the IP address and
+//! the port as u16.
+//!
+//! Datagrams are recorded as received, even if they are not valid CoE [Packet]s, so that broken
+//! traffic can be reproduced as well.
+
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    vec,
+    vec::Vec,
+};
+
+use crate::{Packet, ParseCOEError};
+
+/// The magic bytes at the start of every capture file.
+const MAGIC: &[u8; 6] = b"COECAP";
+/// The version of the capture format written by [CaptureWriter].
+const VERSION: u8 = 1;
+
+/// The Errors that can occur when reading, writing or replaying a capture.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// Reading from or writing to the file or socket failed.
+    Io(io::Error),
+    /// The file does not start with the capture magic.
+    NotACapture,
+    /// The capture format version is not supported.
+    VersionNotSupported(u8),
+    /// An address with an unknown family was found.
+    AddressFamilyUnknown(u8),
+    /// The file ended in the middle of a record.
+    Truncated,
+    /// A datagram is longer then the 65535 bytes a record can hold.
+    DatagramTooLong(usize),
+    /// A timestamp was before the unix epoch or too far in the future.
+    TimestampOutOfRange(SystemTime),
+    /// The replay speed is not positive, or too small to delay the datagrams by.
+    SpeedInvalid(f64),
+}
+impl core::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO Error: {e}"),
+            Self::NotACapture => write!(f, "The file is not a CoE capture."),
+            Self::VersionNotSupported(x) => {
+                write!(f, "The capture format version {x} is not supported.")
+            }
+            Self::AddressFamilyUnknown(x) => {
+                write!(f, "The address family {x} is not known.")
+            }
+            Self::Truncated => write!(f, "The capture ended in the middle of a record."),
+            Self::DatagramTooLong(x) => {
+                write!(
+                    f,
+                    "The datagram is {x} bytes long, at most 65535 can be stored."
+                )
+            }
+            Self::TimestampOutOfRange(x) => {
+                write!(f, "The timestamp {x:?} cannot be stored.")
+            }
+            Self::SpeedInvalid(x) => write!(f, "The replay speed {x} is not usable."),
+        }
+    }
+}
+impl std::error::Error for CaptureError {}
+impl From<io::Error> for CaptureError {
+    fn from(value: io::Error) -> Self {
+        if value.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+/// A single datagram in a capture.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CaptureRecord {
+    /// When the datagram was received, with microsecond precision.
+    pub timestamp: SystemTime,
+    /// The sender of the datagram.
+    pub source: SocketAddr,
+    /// The address of the receiving socket, as it was bound.
+    ///
+    /// This is not necessarily the address the datagram was sent to: a socket bound to
+    /// `0.0.0.0:5441` records `0.0.0.0:5441` for datagrams to any local address, including
+    /// broadcasts.
+    pub local_address: SocketAddr,
+    /// The raw datagram.
+    pub data: Vec<u8>,
+}
+impl CaptureRecord {
+    /// Create a record containing the serialization of `packet`.
+    pub fn from_packet(
+        timestamp: SystemTime,
+        source: SocketAddr,
+        local_address: SocketAddr,
+        packet: &Packet,
+    ) -> CaptureRecord {
+        CaptureRecord {
+            timestamp,
+            source,
+            local_address,
+            data: packet.serialize_into_vec(),
+        }
+    }
+
+    /// Parse the recorded datagram.
+    pub fn packet(&self) -> Result<Packet, ParseCOEError> {
+        Packet::try_from(&self.data[..])
+    }
+}
+
+/// Writes [CaptureRecord]s into a capture file.
+///
+/// The header is written immediately when creating the writer.
+/// ```rust
+/// # use coe::{CaptureReader, CaptureRecord, CaptureWriter, Packet};
+/// use std::time::SystemTime;
+/// let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+/// let record = CaptureRecord::from_packet(
+///     SystemTime::UNIX_EPOCH,
+///     "192.168.1.2:5442".parse().unwrap(),
+///     "192.168.1.3:5442".parse().unwrap(),
+///     &Packet::new(),
+/// );
+/// writer.write_record(&record).unwrap();
+/// let file = writer.into_inner();
+///
+/// let mut reader = CaptureReader::new(&file[..]).unwrap();
+/// assert_eq!(reader.next().unwrap().unwrap(), record);
+/// assert!(reader.next().is_none());
+/// ```
+pub struct CaptureWriter<W: Write> {
+    inner: W,
+}
+impl<W: Write> CaptureWriter<W> {
+    /// Start a new capture, writing the header into `inner`.
+    pub fn new(mut inner: W) -> Result<CaptureWriter<W>, CaptureError> {
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION, 0])?;
+        Ok(CaptureWriter { inner })
+    }
+
+    /// Append a record.
+    pub fn write_record(&mut self, record: &CaptureRecord) -> Result<(), CaptureError> {
+        let length = u16::try_from(record.data.len())
+            .map_err(|_| CaptureError::DatagramTooLong(record.data.len()))?;
+        let micros = record
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|x| u64::try_from(x.as_micros()).ok())
+            .ok_or(CaptureError::TimestampOutOfRange(record.timestamp))?;
+        self.inner.write_all(&micros.to_le_bytes())?;
+        write_address(&mut self.inner, record.source)?;
+        write_address(&mut self.inner, record.local_address)?;
+        self.inner.write_all(&length.to_le_bytes())?;
+        self.inner.write_all(&record.data)?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> Result<(), CaptureError> {
+        Ok(self.inner.flush()?)
+    }
+
+    /// Get the underlying writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+fn write_address<W: Write>(w: &mut W, address: SocketAddr) -> io::Result<()> {
+    match address.ip() {
+        IpAddr::V4(ip) => {
+            w.write_all(&[4])?;
+            w.write_all(&ip.octets())?;
+        }
+        IpAddr::V6(ip) => {
+            w.write_all(&[6])?;
+            w.write_all(&ip.octets())?;
+        }
+    };
+    w.write_all(&address.port().to_le_bytes())
+}
+
+/// Reads [CaptureRecord]s from a capture file.
+///
+/// The header is validated when creating the reader. Iterating yields the records in order and
+/// stops at the end of the file or after the first error.
+pub struct CaptureReader<R: Read> {
+    inner: R,
+    failed: bool,
+}
+impl<R: Read> CaptureReader<R> {
+    /// Read and validate the header of the capture in `inner`.
+    pub fn new(mut inner: R) -> Result<CaptureReader<R>, CaptureError> {
+        let mut header = [0_u8; 8];
+        inner.read_exact(&mut header).map_err(|e| {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                CaptureError::NotACapture
+            } else {
+                CaptureError::Io(e)
+            }
+        })?;
+        if &header[0..6] != MAGIC {
+            return Err(CaptureError::NotACapture);
+        };
+        if header[6] != VERSION {
+            return Err(CaptureError::VersionNotSupported(header[6]));
+        };
+        Ok(CaptureReader {
+            inner,
+            failed: false,
+        })
+    }
+
+    /// Read the next record. Returns `Ok(None)` at the end of the file.
+    pub fn read_record(&mut self) -> Result<Option<CaptureRecord>, CaptureError> {
+        let mut timestamp = [0_u8; 8];
+        // only a file ending exactly between two records is a clean end
+        let mut filled = 0;
+        while filled < timestamp.len() {
+            match self.inner.read(&mut timestamp[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(CaptureError::Truncated),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            };
+        }
+        let source = read_address(&mut self.inner)?;
+        let local_address = read_address(&mut self.inner)?;
+        let mut length = [0_u8; 2];
+        self.inner.read_exact(&mut length)?;
+        let mut data = vec![0_u8; usize::from(u16::from_le_bytes(length))];
+        self.inner.read_exact(&mut data)?;
+        Ok(Some(CaptureRecord {
+            timestamp: UNIX_EPOCH + Duration::from_micros(u64::from_le_bytes(timestamp)),
+            source,
+            local_address,
+            data,
+        }))
+    }
+}
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CaptureRecord, CaptureError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        };
+        let res = self.read_record().transpose();
+        self.failed = matches!(res, Some(Err(_)));
+        res
+    }
+}
+
+fn read_address<R: Read>(r: &mut R) -> Result<SocketAddr, CaptureError> {
+    let mut family = [0_u8; 1];
+    r.read_exact(&mut family)?;
+    let ip = match family[0] {
+        4 => {
+            let mut octets = [0_u8; 4];
+            r.read_exact(&mut octets)?;
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        6 => {
+            let mut octets = [0_u8; 16];
+            r.read_exact(&mut octets)?;
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        x => return Err(CaptureError::AddressFamilyUnknown(x)),
+    };
+    let mut port = [0_u8; 2];
+    r.read_exact(&mut port)?;
+    Ok(SocketAddr::new(ip, u16::from_le_bytes(port)))
+}
+
+/// Receive datagrams on `socket` and append them to `writer`, flushing after every datagram.
+///
+/// Every record gets the address `socket` is bound to as its [CaptureRecord::local_address].
+///
+/// Stops after `limit` datagrams if a limit is given, otherwise only on error. Returns the number
+/// of datagrams recorded.
+pub fn record_capture<W: Write>(
+    socket: &UdpSocket,
+    writer: &mut CaptureWriter<W>,
+    limit: Option<usize>,
+) -> Result<usize, CaptureError> {
+    let local_address = socket.local_addr()?;
+    // datagrams that are not CoE are recorded as well, so accept everything UDP can carry
+    let mut buf = vec![0_u8; usize::from(u16::MAX)];
+    let mut count = 0;
+    while limit != Some(count) {
+        let (length, source) = socket.recv_from(&mut buf)?;
+        writer.write_record(&CaptureRecord {
+            timestamp: SystemTime::now(),
+            source,
+            local_address,
+            data: buf[..length].to_vec(),
+        })?;
+        writer.flush()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Send the datagrams in `records` from `socket` to `target`, keeping their original timing.
+///
+/// The delays between datagrams are divided by `speed`, so `2.0` replays twice as fast and
+/// [f64::INFINITY] sends all datagrams without delay. Returns the number of datagrams sent.
+///
+/// Fails with [CaptureError::SpeedInvalid] if `speed` is not positive, or so small that a delay
+/// can no longer be represented.
+pub fn replay_capture<I>(
+    records: I,
+    socket: &UdpSocket,
+    target: SocketAddr,
+    speed: f64,
+) -> Result<usize, CaptureError>
+where
+    I: IntoIterator<Item = Result<CaptureRecord, CaptureError>>,
+{
+    if speed.is_nan() || speed <= 0.0 {
+        return Err(CaptureError::SpeedInvalid(speed));
+    };
+    let mut start: Option<(Instant, SystemTime)> = None;
+    let mut count = 0;
+    for record in records {
+        let record = record?;
+        match start {
+            None => start = Some((Instant::now(), record.timestamp)),
+            Some((started, first)) => {
+                // out-of-order timestamps are sent immediately
+                let offset = record
+                    .timestamp
+                    .duration_since(first)
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    / speed;
+                let offset = Duration::try_from_secs_f64(offset)
+                    .map_err(|_| CaptureError::SpeedInvalid(speed))?;
+                let elapsed = started.elapsed();
+                if offset > elapsed {
+                    thread::sleep(offset - elapsed);
+                };
+            }
+        };
+        socket.send_to(&record.data, target)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, Payload};
+
+    fn record(secs: u64, data: Vec<u8>) -> CaptureRecord {
+        CaptureRecord {
+            timestamp: UNIX_EPOCH + Duration::from_micros(secs * 1_000_000 + 123),
+            source: "192.168.1.2:5442".parse().unwrap(),
+            local_address: "[fe80::1]:5441".parse().unwrap(),
+            data,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let packet = Packet::try_from_payloads(&[Payload::new(
+            58,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::Watt(10)),
+        )])
+        .unwrap();
+        let records = [
+            record(1, packet.serialize_into_vec()),
+            record(2, vec![1, 2, 3]),
+            record(3, vec![]),
+        ];
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        for r in &records {
+            writer.write_record(r).unwrap();
+        }
+        let file = writer.into_inner();
+        let read = CaptureReader::new(&file[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, records);
+        assert_eq!(read[0].packet(), Ok(packet));
+        assert_eq!(
+            read[1].packet(),
+            Err(ParseCOEError::PacketBelowHeaderLength)
+        );
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(matches!(
+            CaptureReader::new(&b"COECA"[..]),
+            Err(CaptureError::NotACapture)
+        ));
+        assert!(matches!(
+            CaptureReader::new(&b"PCAPNG\x01\x00"[..]),
+            Err(CaptureError::NotACapture)
+        ));
+        assert!(matches!(
+            CaptureReader::new(&b"COECAP\x02\x00"[..]),
+            Err(CaptureError::VersionNotSupported(2))
+        ));
+
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        writer.write_record(&record(1, vec![1, 2, 3])).unwrap();
+        let mut file = writer.into_inner();
+        file.pop();
+        let mut reader = CaptureReader::new(&file[..]).unwrap();
+        assert!(matches!(reader.next(), Some(Err(CaptureError::Truncated))));
+        assert!(reader.next().is_none());
+
+        file[8 + 8] = 5;
+        let mut reader = CaptureReader::new(&file[..]).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(CaptureError::AddressFamilyUnknown(5)))
+        ));
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let records = [record(1, vec![1]), record(2, vec![2, 2]), record(4, vec![])];
+
+        let started = Instant::now();
+        let sent = replay_capture(
+            records.iter().cloned().map(Ok),
+            &sender,
+            receiver.local_addr().unwrap(),
+            100.0,
+        )
+        .unwrap();
+        assert_eq!(sent, 3);
+        // 3 seconds of traffic at 100 times the speed
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        assert_eq!(record_capture(&receiver, &mut writer, Some(3)).unwrap(), 3);
+        let file = writer.into_inner();
+        let recorded = CaptureReader::new(&file[..])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            recorded.iter().map(|r| &r.data[..]).collect::<Vec<_>>(),
+            [&[1][..], &[2, 2], &[]]
+        );
+        assert_eq!(recorded[0].source, sender.local_addr().unwrap());
+        assert_eq!(recorded[0].local_address, receiver.local_addr().unwrap());
+    }
+
+    #[test]
+    fn replay_invalid_speed() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = sender.local_addr().unwrap();
+        let records = [record(1, vec![1]), record(2, vec![2])];
+        for speed in [0.0, -1.0, f64::NAN, f64::NEG_INFINITY, 1e-300] {
+            assert!(matches!(
+                replay_capture(records.iter().cloned().map(Ok), &sender, target, speed),
+                Err(CaptureError::SpeedInvalid(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Conversions between the date and time units and [chrono].
+//!
+//! A [NaiveDate] converts into [AnalogueCOEValue::Date], or into [DayOfMonth] and [MonthOfYear]
+//! for [AnalogueCOEValue::DayOfMonth] and [AnalogueCOEValue::MonthOfYear]:
+//! ```rust
+//! # use coe::{AnalogueCOEValue, DayOfMonth};
+//! use chrono::NaiveDate;
+//! let today = NaiveDate::from_ymd_opt(2024, 6, 17).unwrap();
+//! let val = AnalogueCOEValue::from(DayOfMonth::from(today));
+//! assert_eq!(val, AnalogueCOEValue::DayOfMonth(171));
+//! ```
+//! Only [AnalogueCOEValue::Date] and [AnalogueCOEValue::Time] carry enough information to be
+//! converted back into a [NaiveDate] or [NaiveTime].
+
+use ::chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
+
+use crate::{AnalogueCOEValue, DateTimeConversionError, DayOfMonth, MonthOfYear};
+
+/// Convert a [NaiveDate] into [AnalogueCOEValue::Date].
+///
+/// Fails if the year is not in `0..=65535`.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use chrono::NaiveDate;
+/// let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+/// assert_eq!(AnalogueCOEValue::try_from(date), Ok(AnalogueCOEValue::Date(29, 2, 2024)));
+/// ```
+impl TryFrom<NaiveDate> for AnalogueCOEValue {
+    type Error = DateTimeConversionError;
+    fn try_from(value: NaiveDate) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year()))?;
+        Ok(AnalogueCOEValue::Date(
+            value.day().try_into().expect("Day of month is at most 31"),
+            value.month().try_into().expect("Month is at most 12"),
+            year,
+        ))
+    }
+}
+
+/// Convert a [NaiveDate] into its [DayOfMonth], dropping the year.
+impl From<NaiveDate> for DayOfMonth {
+    fn from(value: NaiveDate) -> Self {
+        DayOfMonth::new(
+            value.day().try_into().expect("Day of month is at most 31"),
+            value.month().try_into().expect("Month is at most 12"),
+        )
+        .expect("A NaiveDate exists in the calendar")
+    }
+}
+
+/// Convert a [NaiveDate] into its [MonthOfYear], dropping the day.
+///
+/// Fails if the year is not in `0..=65535`.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, MonthOfYear};
+/// use chrono::NaiveDate;
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 17).unwrap();
+/// let val = MonthOfYear::try_from(date).map(AnalogueCOEValue::from);
+/// assert_eq!(val, Ok(AnalogueCOEValue::MonthOfYear(2024 * 12 + 5)));
+/// ```
+impl TryFrom<NaiveDate> for MonthOfYear {
+    type Error = DateTimeConversionError;
+    fn try_from(value: NaiveDate) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year()))?;
+        Ok(
+            MonthOfYear::new(value.month().try_into().expect("Month is at most 12"), year)
+                .expect("Month is in 1..=12"),
+        )
+    }
+}
+
+/// Convert [AnalogueCOEValue::Date] into a [NaiveDate].
+///
+/// Fails for other units and for dates which do not exist in the calendar.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use chrono::NaiveDate;
+/// let date = NaiveDate::try_from(AnalogueCOEValue::Date(17, 6, 2024));
+/// assert_eq!(date, Ok(NaiveDate::from_ymd_opt(2024, 6, 17).unwrap()));
+///
+/// let date = NaiveDate::try_from(AnalogueCOEValue::Date(30, 2, 2024));
+/// assert_eq!(date, Err(DateTimeConversionError::InvalidDate(30, 2, 2024)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for NaiveDate {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Date(days, months, years) => {
+                NaiveDate::from_ymd_opt(i32::from(years), u32::from(months), u32::from(days))
+                    .ok_or(DateTimeConversionError::InvalidDate(days, months, years))
+            }
+            _ => Err(DateTimeConversionError::NotDate),
+        }
+    }
+}
+
+/// Convert a [NaiveTime] into [AnalogueCOEValue::Time].
+///
+/// CoE only transmits hours and minutes, seconds are truncated.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use chrono::NaiveTime;
+/// let time = NaiveTime::from_hms_opt(13, 37, 59).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(time), AnalogueCOEValue::Time(13 * 60 + 37));
+/// ```
+impl From<NaiveTime> for AnalogueCOEValue {
+    fn from(value: NaiveTime) -> Self {
+        AnalogueCOEValue::Time(
+            i32::try_from(value.hour() * 60 + value.minute())
+                .expect("Minutes in a day always fit into i32"),
+        )
+    }
+}
+
+/// Convert [AnalogueCOEValue::Time] into a [NaiveTime].
+///
+/// Fails for other units and for values outside of 00:00-23:59.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use chrono::NaiveTime;
+/// let time = NaiveTime::try_from(AnalogueCOEValue::Time(13 * 60 + 37));
+/// assert_eq!(time, Ok(NaiveTime::from_hms_opt(13, 37, 0).unwrap()));
+///
+/// let time = NaiveTime::try_from(AnalogueCOEValue::Time(24 * 60));
+/// assert_eq!(time, Err(DateTimeConversionError::InvalidTime(24 * 60)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for NaiveTime {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Time(x) => {
+                if !(0..24 * 60).contains(&x) {
+                    return Err(DateTimeConversionError::InvalidTime(x));
+                };
+                let minutes = u32::try_from(x).expect("x should be checked to be positive");
+                NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+                    .ok_or(DateTimeConversionError::InvalidTime(x))
+            }
+            _ => Err(DateTimeConversionError::NotTime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn date_roundtrip() {
+        let date = NaiveDate::from_ymd_opt(1999, 12, 31).unwrap();
+        let val = AnalogueCOEValue::try_from(date).unwrap();
+        assert_eq!(val, AnalogueCOEValue::Date(31, 12, 1999));
+        assert_eq!(NaiveDate::try_from(val), Ok(date));
+    }
+
+    #[test]
+    fn date_rejects_non_leap_year() {
+        assert_eq!(
+            NaiveDate::try_from(AnalogueCOEValue::Date(29, 2, 2023)),
+            Err(DateTimeConversionError::InvalidDate(29, 2, 2023))
+        );
+    }
+
+    #[test]
+    fn date_year_out_of_range() {
+        let date = NaiveDate::from_ymd_opt(-1, 1, 1).unwrap();
+        assert_eq!(
+            AnalogueCOEValue::try_from(date),
+            Err(DateTimeConversionError::YearOutOfRange(-1))
+        );
+    }
+
+    #[test]
+    fn day_and_month() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(DayOfMonth::from(date), DayOfMonth::new(29, 2).unwrap());
+        assert_eq!(
+            MonthOfYear::try_from(date),
+            Ok(MonthOfYear::new(2, 2024).unwrap())
+        );
+        let date = NaiveDate::from_ymd_opt(-1, 1, 1).unwrap();
+        assert_eq!(
+            MonthOfYear::try_from(date),
+            Err(DateTimeConversionError::YearOutOfRange(-1))
+        );
+    }
+
+    #[test]
+    fn wrong_units() {
+        assert_eq!(
+            NaiveDate::try_from(AnalogueCOEValue::Time(12)),
+            Err(DateTimeConversionError::NotDate)
+        );
+        assert_eq!(
+            NaiveTime::try_from(AnalogueCOEValue::Date(1, 1, 2000)),
+            Err(DateTimeConversionError::NotTime)
+        );
+    }
+
+    #[test]
+    fn time_bounds() {
+        assert_eq!(
+            NaiveTime::try_from(AnalogueCOEValue::Time(-1)),
+            Err(DateTimeConversionError::InvalidTime(-1))
+        );
+        assert_eq!(
+            NaiveTime::try_from(AnalogueCOEValue::Time(24 * 60 - 1)),
+            Ok(NaiveTime::from_hms_opt(23, 59, 0).unwrap())
+        );
+    }
+}
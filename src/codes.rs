@@ -0,0 +1,393 @@
+//! Typed representations of the units that transmit coded values instead of measurements.
+//!
+//! The codes follow the function help of TA's heating circuit and blinds controllers. Codes that
+//! are not documented there are kept in an `Unknown` variant, so that every value received can be
+//! forwarded unchanged. An [UnknownCode] can only be created from codes that are not documented,
+//! so that converting a value into a code and back, or a code into a value and back, never
+//! changes it.
+
+use crate::{AnalogueCOEValue, DigitalCOEValue};
+
+/// The Errors that can occur when reading a coded value.
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CodedValueError {
+    /// The value has the unit with this ID, which does not contain the requested code.
+    WrongUnit(u8),
+    /// The value is not a valid code for this unit.
+    ValueOutOfBounds(i32),
+}
+impl core::fmt::Display for CodedValueError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::WrongUnit(x) => write!(f, "The unit with ID {x} does not contain this code."),
+            Self::ValueOutOfBounds(x) => write!(f, "The value {x} is not a valid code."),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for CodedValueError {}
+
+/// A code that is not documented for its unit, kept to forward it unchanged.
+///
+/// This is only created by the `From<i32>` implementations of the coded units, which never wrap a
+/// documented code.
+/// ```rust
+/// # use coe::{Blinds, HeatingCircuitOpMode};
+/// let HeatingCircuitOpMode::Unknown(code) = HeatingCircuitOpMode::from(7) else {
+///     unreachable!()
+/// };
+/// assert_eq!(code.code(), 7);
+/// assert_eq!(Blinds::from(0), Blinds::Stop);
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnknownCode(i32);
+impl UnknownCode {
+    /// The code as sent on-wire.
+    pub const fn code(&self) -> i32 {
+        self.0
+    }
+}
+impl From<UnknownCode> for i32 {
+    fn from(value: UnknownCode) -> Self {
+        value.0
+    }
+}
+
+/// The operating mode selected for a heating circuit ([AnalogueCOEValue::HeatingCircuitOpMode]).
+///
+/// ```rust
+/// # use coe::{AnalogueCOEValue, HeatingCircuitOpMode};
+/// let val = AnalogueCOEValue::from(HeatingCircuitOpMode::Lowered);
+/// assert_eq!(val, AnalogueCOEValue::HeatingCircuitOpMode(2));
+/// assert_eq!(HeatingCircuitOpMode::try_from(val), Ok(HeatingCircuitOpMode::Lowered));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(from = "i32", into = "i32"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HeatingCircuitOpMode {
+    /// Follow the time program.
+    Time,
+    /// Always heat to the normal room temperature.
+    Normal,
+    /// Always heat to the lowered room temperature.
+    Lowered,
+    /// Do not heat, except for frost protection.
+    Standby,
+    /// Any other code.
+    Unknown(UnknownCode),
+}
+impl From<i32> for HeatingCircuitOpMode {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Time,
+            1 => Self::Normal,
+            2 => Self::Lowered,
+            3 => Self::Standby,
+            x => Self::Unknown(UnknownCode(x)),
+        }
+    }
+}
+impl From<HeatingCircuitOpMode> for i32 {
+    fn from(value: HeatingCircuitOpMode) -> Self {
+        match value {
+            HeatingCircuitOpMode::Time => 0,
+            HeatingCircuitOpMode::Normal => 1,
+            HeatingCircuitOpMode::Lowered => 2,
+            HeatingCircuitOpMode::Standby => 3,
+            HeatingCircuitOpMode::Unknown(x) => x.0,
+        }
+    }
+}
+impl From<HeatingCircuitOpMode> for AnalogueCOEValue {
+    fn from(value: HeatingCircuitOpMode) -> Self {
+        AnalogueCOEValue::HeatingCircuitOpMode(value.into())
+    }
+}
+impl TryFrom<AnalogueCOEValue> for HeatingCircuitOpMode {
+    type Error = CodedValueError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::HeatingCircuitOpMode(x) => Ok(x.into()),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
+
+/// The level a heating circuit currently runs at ([AnalogueCOEValue::HeatingCircuitOpLevel]).
+///
+/// Unlike [HeatingCircuitOpMode], this is the result of evaluating the mode, time program and
+/// external inputs.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, HeatingCircuitOpLevel};
+/// let val = AnalogueCOEValue::HeatingCircuitOpLevel(2);
+/// assert_eq!(HeatingCircuitOpLevel::try_from(val), Ok(HeatingCircuitOpLevel::Normal));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(from = "i32", into = "i32"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HeatingCircuitOpLevel {
+    /// The heating circuit is off.
+    Standby,
+    /// Heating to the lowered room temperature.
+    Lowered,
+    /// Heating to the normal room temperature.
+    Normal,
+    /// Heating to protect from frost only.
+    FrostProtection,
+    /// Any other code.
+    Unknown(UnknownCode),
+}
+impl From<i32> for HeatingCircuitOpLevel {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Standby,
+            1 => Self::Lowered,
+            2 => Self::Normal,
+            3 => Self::FrostProtection,
+            x => Self::Unknown(UnknownCode(x)),
+        }
+    }
+}
+impl From<HeatingCircuitOpLevel> for i32 {
+    fn from(value: HeatingCircuitOpLevel) -> Self {
+        match value {
+            HeatingCircuitOpLevel::Standby => 0,
+            HeatingCircuitOpLevel::Lowered => 1,
+            HeatingCircuitOpLevel::Normal => 2,
+            HeatingCircuitOpLevel::FrostProtection => 3,
+            HeatingCircuitOpLevel::Unknown(x) => x.0,
+        }
+    }
+}
+impl From<HeatingCircuitOpLevel> for AnalogueCOEValue {
+    fn from(value: HeatingCircuitOpLevel) -> Self {
+        AnalogueCOEValue::HeatingCircuitOpLevel(value.into())
+    }
+}
+impl TryFrom<AnalogueCOEValue> for HeatingCircuitOpLevel {
+    type Error = CodedValueError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::HeatingCircuitOpLevel(x) => Ok(x.into()),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
+
+/// The movement of a blind drive ([AnalogueCOEValue::Blinds]).
+///
+/// ```rust
+/// # use coe::{AnalogueCOEValue, Blinds};
+/// assert_eq!(AnalogueCOEValue::from(Blinds::Down), AnalogueCOEValue::Blinds(2));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(from = "i32", into = "i32"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Blinds {
+    /// The blind does not move.
+    Stop,
+    /// The blind moves up (opens).
+    Up,
+    /// The blind moves down (closes).
+    Down,
+    /// Any other code.
+    Unknown(UnknownCode),
+}
+impl From<i32> for Blinds {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Stop,
+            1 => Self::Up,
+            2 => Self::Down,
+            x => Self::Unknown(UnknownCode(x)),
+        }
+    }
+}
+impl From<Blinds> for i32 {
+    fn from(value: Blinds) -> Self {
+        match value {
+            Blinds::Stop => 0,
+            Blinds::Up => 1,
+            Blinds::Down => 2,
+            Blinds::Unknown(x) => x.0,
+        }
+    }
+}
+impl From<Blinds> for AnalogueCOEValue {
+    fn from(value: Blinds) -> Self {
+        AnalogueCOEValue::Blinds(value.into())
+    }
+}
+impl TryFrom<AnalogueCOEValue> for Blinds {
+    type Error = CodedValueError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Blinds(x) => Ok(x.into()),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
+
+/// The position of a blind ([AnalogueCOEValue::BlindsPosition]).
+///
+/// On-wire, the first byte contains the height and the second byte the slat angle, both in
+/// percent. `0` is fully open and `100` fully closed.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, BlindsPosition};
+/// let pos = BlindsPosition::new(75, 50).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(pos), AnalogueCOEValue::BlindsPosition(75 + 50 * 256));
+/// assert_eq!(BlindsPosition::try_from(AnalogueCOEValue::BlindsPosition(75 + 50 * 256)), Ok(pos));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BlindsPosition {
+    /// Height in percent
+    position: u8,
+    /// Slat angle in percent
+    slat: u8,
+}
+impl BlindsPosition {
+    /// Create a position from height and slat angle in percent.
+    ///
+    /// Returns `None` if either is larger then 100.
+    pub fn new(position: u8, slat: u8) -> Option<BlindsPosition> {
+        if position > 100 || slat > 100 {
+            return None;
+        };
+        Some(BlindsPosition { position, slat })
+    }
+
+    /// The height in percent (0: open, 100: closed)
+    pub fn position(&self) -> u8 {
+        self.position
+    }
+
+    /// The slat angle in percent (0: open, 100: closed)
+    pub fn slat(&self) -> u8 {
+        self.slat
+    }
+}
+impl TryFrom<i32> for BlindsPosition {
+    type Error = CodedValueError;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        let bytes = value.to_le_bytes();
+        if bytes[2] != 0 || bytes[3] != 0 {
+            return Err(CodedValueError::ValueOutOfBounds(value));
+        };
+        BlindsPosition::new(bytes[0], bytes[1]).ok_or(CodedValueError::ValueOutOfBounds(value))
+    }
+}
+impl From<BlindsPosition> for i32 {
+    fn from(value: BlindsPosition) -> Self {
+        i32::from(value.position) + (i32::from(value.slat) << 8)
+    }
+}
+impl From<BlindsPosition> for AnalogueCOEValue {
+    fn from(value: BlindsPosition) -> Self {
+        AnalogueCOEValue::BlindsPosition(value.into())
+    }
+}
+impl TryFrom<AnalogueCOEValue> for BlindsPosition {
+    type Error = CodedValueError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::BlindsPosition(x) => x.try_into(),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
+
+/// The state of a mixer ([DigitalCOEValue::Mixer]).
+///
+/// ```rust
+/// # use coe::{DigitalCOEValue, MixerMode};
+/// assert_eq!(DigitalCOEValue::from(MixerMode::Normal), DigitalCOEValue::Mixer(true));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MixerMode {
+    /// The mixer is switched off (`false` on-wire).
+    Off,
+    /// The mixer runs normally (`true` on-wire).
+    Normal,
+}
+impl From<bool> for MixerMode {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::Normal
+        } else {
+            Self::Off
+        }
+    }
+}
+impl From<MixerMode> for bool {
+    fn from(value: MixerMode) -> Self {
+        value == MixerMode::Normal
+    }
+}
+impl From<MixerMode> for DigitalCOEValue {
+    fn from(value: MixerMode) -> Self {
+        DigitalCOEValue::Mixer(value.into())
+    }
+}
+impl TryFrom<DigitalCOEValue> for MixerMode {
+    type Error = CodedValueError;
+    fn try_from(value: DigitalCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            DigitalCOEValue::Mixer(x) => Ok(x.into()),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
+
+/// The mode of a room sensor ([DigitalCOEValue::RASMode]).
+///
+/// ```rust
+/// # use coe::{DigitalCOEValue, RasMode};
+/// assert_eq!(RasMode::try_from(DigitalCOEValue::RASMode(false)), Ok(RasMode::Lowered));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RasMode {
+    /// The room sensor requests the lowered temperature (`false` on-wire).
+    Lowered,
+    /// The room sensor requests the normal temperature (`true` on-wire).
+    Normal,
+}
+impl From<bool> for RasMode {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::Normal
+        } else {
+            Self::Lowered
+        }
+    }
+}
+impl From<RasMode> for bool {
+    fn from(value: RasMode) -> Self {
+        value == RasMode::Normal
+    }
+}
+impl From<RasMode> for DigitalCOEValue {
+    fn from(value: RasMode) -> Self {
+        DigitalCOEValue::RASMode(value.into())
+    }
+}
+impl TryFrom<DigitalCOEValue> for RasMode {
+    type Error = CodedValueError;
+    fn try_from(value: DigitalCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            DigitalCOEValue::RASMode(x) => Ok(x.into()),
+            x => Err(CodedValueError::WrongUnit(x.unit_id())),
+        }
+    }
+}
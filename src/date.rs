@@ -0,0 +1,523 @@
+//! Calendar-validated helpers for the date units.
+//!
+//! CoE knows three units containing dates: [AnalogueCOEValue::DayOfMonth],
+//! [AnalogueCOEValue::MonthOfYear] and [AnalogueCOEValue::Date].
+//! TA-Hardware accepts any value in these units, including days that do not exist, like the 30th
+//! of february. The helpers in this module never create such values.
+//!
+//! [AnalogueCOEValue::Date] is additionally validated when parsing a [Payload](crate::Payload)
+//! and when adding it to a [Packet](crate::Packet). Use
+//! [Packet::try_from_permissive](crate::Packet::try_from_permissive) to parse every date
+//! TA-Hardware accepts instead.
+
+use crate::AnalogueCOEValue;
+
+/// The unit ID of [AnalogueCOEValue::Date], the only unit which is not a plain `i32`.
+pub(crate) const DATE_UNIT_ID: u8 = AnalogueCOEValue::Date(1, 1, 0).unit_id();
+
+/// The Errors that can occur when validating a date.
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DateError {
+    /// The month is not in 1-12.
+    MonthOutOfRange(u8),
+    /// The day (first u8) does not exist in the month (second u8).
+    DayOutOfRange(u8, u8),
+    /// The 29th of february was requested in a year that is not a leap year.
+    NotALeapYear(u16),
+}
+impl core::fmt::Display for DateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::MonthOutOfRange(x) => {
+                write!(f, "The month must be in 1-12, but {x} was supplied.")
+            }
+            Self::DayOutOfRange(day, month) => {
+                write!(f, "Month {month} does not have a day {day}.")
+            }
+            Self::NotALeapYear(year) => {
+                write!(f, "{year} is not a leap year and has no 29th of february.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for DateError {}
+
+/// Returns whether `year` is a leap year in the gregorian calendar.
+///
+/// Example:
+/// ```rust
+/// # use coe::is_leap_year;
+/// assert!(is_leap_year(2024));
+/// assert!(is_leap_year(2000));
+/// assert!(!is_leap_year(1900));
+/// assert!(!is_leap_year(2023));
+/// ```
+pub const fn is_leap_year(year: u16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// The number of days in `month` of `year`.
+///
+/// Example:
+/// ```rust
+/// # use coe::{days_in_month, DateError};
+/// assert_eq!(days_in_month(2, 2024), Ok(29));
+/// assert_eq!(days_in_month(2, 2023), Ok(28));
+/// assert_eq!(days_in_month(13, 2023), Err(DateError::MonthOutOfRange(13)));
+/// ```
+pub const fn days_in_month(month: u8, year: u16) -> Result<u8, DateError> {
+    match month {
+        2 if !is_leap_year(year) => Ok(28),
+        _ => max_days_in_month(month),
+    }
+}
+
+/// The number of days in `month` of a leap year.
+const fn max_days_in_month(month: u8) -> Result<u8, DateError> {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => Ok(31),
+        4 | 6 | 9 | 11 => Ok(30),
+        2 => Ok(29),
+        _ => Err(DateError::MonthOutOfRange(month)),
+    }
+}
+
+/// Check whether `day`.`month`.`year` exists in the gregorian calendar.
+///
+/// Example:
+/// ```rust
+/// # use coe::{validate_date, DateError};
+/// assert_eq!(validate_date(29, 2, 2024), Ok(()));
+/// assert_eq!(validate_date(29, 2, 2023), Err(DateError::NotALeapYear(2023)));
+/// assert_eq!(validate_date(31, 4, 2023), Err(DateError::DayOutOfRange(31, 4)));
+/// assert_eq!(validate_date(0, 4, 2023), Err(DateError::DayOutOfRange(0, 4)));
+/// ```
+pub const fn validate_date(day: u8, month: u8, year: u16) -> Result<(), DateError> {
+    // `?` is not available in const fn
+    if let Err(e) = validate_day_of_month(day, month) {
+        return Err(e);
+    };
+    match days_in_month(month, year) {
+        Ok(days) if day > days => Err(DateError::NotALeapYear(year)),
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check whether `day` exists in `month` of any year.
+const fn validate_day_of_month(day: u8, month: u8) -> Result<(), DateError> {
+    match max_days_in_month(month) {
+        Ok(days) if day == 0 || day > days => Err(DateError::DayOutOfRange(day, month)),
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check an [AnalogueCOEValue::Date] for a valid date.
+///
+/// This is the check applied while parsing and while building [Packet](crate::Packet)s.
+/// Always succeeds for other units.
+pub(crate) const fn check_date(value: &AnalogueCOEValue) -> Result<(), DateError> {
+    match value {
+        AnalogueCOEValue::Date(days, months, years) => validate_date(*days, *months, *years),
+        _ => Ok(()),
+    }
+}
+
+/// Convert a day and month into [AnalogueCOEValue::DayOfMonth].
+///
+/// Fails when the month does not have that day in any year. Because [AnalogueCOEValue::DayOfMonth]
+/// contains no year, the 29th of february is accepted.
+///
+/// Example:
+/// ```rust
+/// use coe::{AnalogueCOEValue, DateError, to_day_of_month};
+/// let val = to_day_of_month(17, 6);
+/// assert_eq!(val, Ok(AnalogueCOEValue::DayOfMonth(171)));
+/// let val = to_day_of_month(58, 6);
+/// assert_eq!(val, Err(DateError::DayOutOfRange(58, 6)));
+/// let val = to_day_of_month(30, 2);
+/// assert_eq!(val, Err(DateError::DayOutOfRange(30, 2)));
+/// let val = to_day_of_month(9, 14);
+/// assert_eq!(val, Err(DateError::MonthOutOfRange(14)));
+/// ```
+pub fn to_day_of_month(day: u8, month: u8) -> Result<AnalogueCOEValue, DateError> {
+    validate_day_of_month(day, month)?;
+    Ok(AnalogueCOEValue::DayOfMonth(
+        i32::from(day - 1) + i32::from(month - 1) * 31,
+    ))
+}
+
+/// The Errors that can occur when parsing an integer as day of month
+#[derive(Hash, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FromDayOfMonthError {
+    /// The supplied AnalogueCOEValue was not DayOfMonth
+    NotDayOfMonth,
+    /// The value is not in bounds for regular FromDayOfMonth or names a day that does not exist.
+    /// Note that TA-Hardware always accepts the Value, but assignes useless values here instead.
+    ValueOutOfBounds(i32),
+}
+impl core::fmt::Display for FromDayOfMonthError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotDayOfMonth => write!(f, "Value was not DayOfMonth"),
+            Self::ValueOutOfBounds(x) => {
+                write!(f, "The Value {x} cannot be parsed as a day, month pair.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FromDayOfMonthError {}
+
+/// Convert the internal format for [AnalogueCOEValue::DayOfMonth] into two u8s containing day and month
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, from_day_of_month, FromDayOfMonthError};
+/// let val = from_day_of_month(AnalogueCOEValue::DayOfMonth(173));
+/// assert_eq!(val, Ok((19, 6)));
+///
+/// let val = from_day_of_month(AnalogueCOEValue::DayOfMonth(-1234));
+/// assert_eq!(val, Err(FromDayOfMonthError::ValueOutOfBounds(-1234)));
+///
+/// // the 30th of february
+/// let val = from_day_of_month(AnalogueCOEValue::DayOfMonth(60));
+/// assert_eq!(val, Err(FromDayOfMonthError::ValueOutOfBounds(60)));
+///
+/// let val = from_day_of_month(AnalogueCOEValue::DegreeKelvin_Tens(123));
+/// assert_eq!(val, Err(FromDayOfMonthError::NotDayOfMonth));
+/// ```
+/// ```rust
+/// use coe::{AnalogueCOEValue, from_day_of_month, to_day_of_month};
+/// assert_eq!(from_day_of_month(to_day_of_month(9, 12).unwrap()), Ok((9, 12)));
+/// ```
+///
+/// # Panics
+///
+/// Panics on programmer errors, when out-of-bounds checks for the contained value are wrong.
+// Why is this not a method on AnalogueCOEValue?
+// This conversion only makes sense when we have a DayOfMonth value.
+// Keeping this function separate prevents IDEs from showing this method for all AnalogueCOEValues.
+pub fn from_day_of_month(value: AnalogueCOEValue) -> Result<(u8, u8), FromDayOfMonthError> {
+    match value {
+        AnalogueCOEValue::DayOfMonth(x) => {
+            if !(0..=30 + 31 * 11).contains(&x) {
+                return Err(FromDayOfMonthError::ValueOutOfBounds(x));
+            };
+            let day = (1 + x % 31).try_into().expect("Modulo 31 yields u8");
+            let month = (1 + x / 31).try_into().expect(
+                "x should be smaller then 31 * 12 such that x / 31 is way smaller then i32::MAX",
+            );
+            validate_day_of_month(day, month)
+                .map_err(|_| FromDayOfMonthError::ValueOutOfBounds(x))?;
+            Ok((day, month))
+        }
+        _ => Err(FromDayOfMonthError::NotDayOfMonth),
+    }
+}
+
+/// Convert a month and year into a [AnalogueCOEValue::MonthOfYear].
+///
+/// Fails when `month` is not in 1-12.
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateError, to_month_of_year};
+/// let val = to_month_of_year(8, 1852);
+/// assert_eq!(val, Ok(AnalogueCOEValue::MonthOfYear(22231)));
+///
+/// let val = to_month_of_year(58, 6);
+/// assert_eq!(val, Err(DateError::MonthOutOfRange(58)));
+/// let val = to_month_of_year(0, 6);
+/// assert_eq!(val, Err(DateError::MonthOutOfRange(0)));
+/// ```
+pub fn to_month_of_year(month: u8, year: u16) -> Result<AnalogueCOEValue, DateError> {
+    if !(1..=12).contains(&month) {
+        return Err(DateError::MonthOutOfRange(month));
+    }
+    Ok(AnalogueCOEValue::MonthOfYear(
+        i32::from(month - 1) + i32::from(year) * 12,
+    ))
+}
+
+/// The Errors that can occur when parsing an integer as day of month
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FromMonthOfYearError {
+    /// The supplied AnalogueCOEValue was not MonthOfYear.
+    NotMonthOfYear,
+    /// The value is not in bounds for regular FromDayOfMonth.
+    /// Note that TA-Hardware always accepts the Value, but assignes useless values here instead.
+    ValueOutOfBounds(i32),
+}
+impl core::fmt::Display for FromMonthOfYearError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotMonthOfYear => write!(f, "Value was not MonthOfYear"),
+            Self::ValueOutOfBounds(x) => {
+                write!(f, "The Value {x} cannot be parsed as a month, year pair.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FromMonthOfYearError {}
+
+/// Convert the internal format for [AnalogueCOEValue::MonthOfYear] into a `u8` and `u16` containing month and year
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, from_month_of_year, FromMonthOfYearError};
+/// let val = from_month_of_year(AnalogueCOEValue::MonthOfYear(22231));
+/// assert_eq!(val, Ok((8, 1852)));
+///
+/// let val = from_month_of_year(AnalogueCOEValue::MonthOfYear(13 * i32::from(u16::MAX)));
+/// assert_eq!(val, Err(FromMonthOfYearError::ValueOutOfBounds(13 * i32::from(u16::MAX))));
+///
+/// let val = from_month_of_year(AnalogueCOEValue::DegreeKelvin_Tens(123));
+/// assert_eq!(val, Err(FromMonthOfYearError::NotMonthOfYear));
+/// ```
+/// ```rust
+/// use coe::{AnalogueCOEValue, from_month_of_year, to_month_of_year};
+/// assert_eq!(from_month_of_year(to_month_of_year(5, 325).unwrap()), Ok((5, 325)));
+/// ```
+///
+/// # Panics
+///
+/// Panics on programmer errors, when out-of-bounds checks for the contained value are wrong.
+// Why is this not a method on AnalogueCOEValue?
+// This conversion only makes sense when we have a MonthOfYear value.
+// Keeping this function separate prevents IDEs from showing this method for all AnalogueCOEValues.
+pub fn from_month_of_year(value: AnalogueCOEValue) -> Result<(u8, u16), FromMonthOfYearError> {
+    match value {
+        AnalogueCOEValue::MonthOfYear(x) => {
+            if (0..=11 + 12 * i32::from(u16::MAX)).contains(&x) {
+                Ok((
+                    (1 + x % 12).try_into().expect("Modulo 12 has image inside u8"),
+                    (x / 12).try_into().expect("x should be smaller then 11 + 12 * u16::MAX and therefore devisible by 12 into a u16"),
+                ))
+            } else {
+                Err(FromMonthOfYearError::ValueOutOfBounds(x))
+            }
+        }
+        _ => Err(FromMonthOfYearError::NotMonthOfYear),
+    }
+}
+
+/// A validated day in a month, without year, as transmitted by [AnalogueCOEValue::DayOfMonth].
+///
+/// With the `chrono`, `time` or `jiff` feature, this can be created from the date types of
+/// these crates.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DayOfMonth};
+/// let day = DayOfMonth::new(17, 6).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(day), AnalogueCOEValue::DayOfMonth(171));
+/// assert_eq!(DayOfMonth::try_from(AnalogueCOEValue::DayOfMonth(171)), Ok(day));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DayOfMonth {
+    month: u8,
+    day: u8,
+}
+impl DayOfMonth {
+    /// Create a day of month. The 29th of february is accepted, since there is no year.
+    pub const fn new(day: u8, month: u8) -> Result<DayOfMonth, DateError> {
+        match validate_day_of_month(day, month) {
+            Ok(()) => Ok(DayOfMonth { month, day }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The day (1-31)
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// The month (1-12)
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+}
+/// Convert into [AnalogueCOEValue::DayOfMonth].
+impl From<DayOfMonth> for AnalogueCOEValue {
+    fn from(value: DayOfMonth) -> Self {
+        AnalogueCOEValue::DayOfMonth(i32::from(value.day - 1) + i32::from(value.month - 1) * 31)
+    }
+}
+/// Read [AnalogueCOEValue::DayOfMonth].
+impl TryFrom<AnalogueCOEValue> for DayOfMonth {
+    type Error = FromDayOfMonthError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        let (day, month) = from_day_of_month(value)?;
+        Ok(DayOfMonth { month, day })
+    }
+}
+
+/// A validated month in a year, as transmitted by [AnalogueCOEValue::MonthOfYear].
+///
+/// With the `chrono`, `time` or `jiff` feature, this can be created from the date types of
+/// these crates.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, MonthOfYear};
+/// let month = MonthOfYear::new(8, 1852).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(month), AnalogueCOEValue::MonthOfYear(22231));
+/// assert_eq!(MonthOfYear::try_from(AnalogueCOEValue::MonthOfYear(22231)), Ok(month));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MonthOfYear {
+    year: u16,
+    month: u8,
+}
+impl MonthOfYear {
+    /// Create a month of year. Fails when `month` is not in 1-12.
+    pub const fn new(month: u8, year: u16) -> Result<MonthOfYear, DateError> {
+        if month == 0 || month > 12 {
+            return Err(DateError::MonthOutOfRange(month));
+        };
+        Ok(MonthOfYear { year, month })
+    }
+
+    /// The month (1-12)
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The year
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+}
+/// Convert into [AnalogueCOEValue::MonthOfYear].
+impl From<MonthOfYear> for AnalogueCOEValue {
+    fn from(value: MonthOfYear) -> Self {
+        AnalogueCOEValue::MonthOfYear(i32::from(value.month - 1) + i32::from(value.year) * 12)
+    }
+}
+/// Read [AnalogueCOEValue::MonthOfYear].
+impl TryFrom<AnalogueCOEValue> for MonthOfYear {
+    type Error = FromMonthOfYearError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        let (month, year) = from_month_of_year(value)?;
+        Ok(MonthOfYear { year, month })
+    }
+}
+
+/// Convert a day, month and year into [AnalogueCOEValue::Date].
+///
+/// Fails when the date does not exist in the gregorian calendar.
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateError, to_date};
+/// let val = to_date(29, 2, 2024);
+/// assert_eq!(val, Ok(AnalogueCOEValue::Date(29, 2, 2024)));
+///
+/// let val = to_date(29, 2, 2023);
+/// assert_eq!(val, Err(DateError::NotALeapYear(2023)));
+/// ```
+pub fn to_date(day: u8, month: u8, year: u16) -> Result<AnalogueCOEValue, DateError> {
+    validate_date(day, month, year)?;
+    Ok(AnalogueCOEValue::Date(day, month, year))
+}
+
+/// The Errors that can occur when reading an [AnalogueCOEValue::Date].
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FromDateError {
+    /// The supplied AnalogueCOEValue was not Date.
+    NotDate,
+    /// The contained date does not exist.
+    InvalidDate(DateError),
+}
+impl core::fmt::Display for FromDateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotDate => write!(f, "Value was not Date"),
+            Self::InvalidDate(e) => write!(f, "Invalid Date: {e}"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FromDateError {}
+
+/// Convert an [AnalogueCOEValue::Date] into day, month and year.
+///
+/// Unlike matching on the value directly, this always validates the date, even for values parsed
+/// with [Packet::try_from_permissive](crate::Packet::try_from_permissive).
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateError, from_date, FromDateError};
+/// let val = from_date(AnalogueCOEValue::Date(17, 6, 2024));
+/// assert_eq!(val, Ok((17, 6, 2024)));
+///
+/// let val = from_date(AnalogueCOEValue::Date(31, 6, 2024));
+/// assert_eq!(val, Err(FromDateError::InvalidDate(DateError::DayOutOfRange(31, 6))));
+///
+/// let val = from_date(AnalogueCOEValue::DegreeKelvin_Tens(123));
+/// assert_eq!(val, Err(FromDateError::NotDate));
+/// ```
+// Not a method on AnalogueCOEValue for the same reason as from_day_of_month.
+pub fn from_date(value: AnalogueCOEValue) -> Result<(u8, u8, u16), FromDateError> {
+    match value {
+        AnalogueCOEValue::Date(days, months, years) => {
+            validate_date(days, months, years).map_err(FromDateError::InvalidDate)?;
+            Ok((days, months, years))
+        }
+        _ => Err(FromDateError::NotDate),
+    }
+}
+
+/// The Errors that can occur when converting date and time units from and to the types of
+/// `chrono`, `time` or `jiff`.
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DateTimeConversionError {
+    /// The supplied AnalogueCOEValue was not [AnalogueCOEValue::Date].
+    NotDate,
+    /// The supplied AnalogueCOEValue was not [AnalogueCOEValue::Time].
+    NotTime,
+    /// The date (days, months, years) does not exist in the calendar, e.g. the 30th of february.
+    InvalidDate(u8, u8, u16),
+    /// The time is not in 00:00-23:59.
+    InvalidTime(i32),
+    /// The year cannot be represented on the other side of the conversion.
+    YearOutOfRange(i32),
+}
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+impl core::fmt::Display for DateTimeConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotDate => write!(f, "Value was not Date"),
+            Self::NotTime => write!(f, "Value was not Time"),
+            Self::InvalidDate(day, month, year) => {
+                write!(f, "{year:04}-{month:02}-{day:02} is not a valid date.")
+            }
+            Self::InvalidTime(x) => {
+                write!(f, "{x} minutes cannot be parsed as a time of day.")
+            }
+            Self::YearOutOfRange(x) => {
+                write!(f, "The year {x} cannot be represented.")
+            }
+        }
+    }
+}
+#[cfg(all(
+    feature = "std",
+    any(feature = "chrono", feature = "time", feature = "jiff")
+))]
+impl std::error::Error for DateTimeConversionError {}
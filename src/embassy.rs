@@ -0,0 +1,240 @@
+//! Send and receive [Packet]s on an embassy-net [UdpSocket].
+//!
+//! This works without `alloc`: the socket buffers are provided by the caller (usually from a
+//! `static`) and [EmbassySocket] (de)serializes through a fixed buffer of the largest CoE packet.
+
+use embassy_net::{
+    udp::{RecvError, SendError, UdpSocket},
+    IpEndpoint,
+};
+
+use crate::{Packet, ParseCOEError};
+
+/// The size of the largest CoE packet: a header and 31 payloads.
+const MAX_PACKET_SIZE: usize = 4 + 31 * 8;
+
+/// The Errors that can occur when sending or receiving [Packet]s with [EmbassySocket].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EmbassyError {
+    /// Sending the datagram failed.
+    Send(SendError),
+    /// Receiving a datagram failed, e.g. because it was longer then any CoE packet.
+    Recv(RecvError),
+    /// A datagram was received, but it is not a valid CoE packet.
+    Parse(ParseCOEError),
+}
+impl core::fmt::Display for EmbassyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Send(e) => write!(f, "Unable to send the packet: {e:?}"),
+            Self::Recv(e) => write!(f, "Unable to receive a packet: {e:?}"),
+            Self::Parse(e) => write!(f, "Received an invalid packet: {e}"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for EmbassyError {}
+impl From<SendError> for EmbassyError {
+    fn from(value: SendError) -> Self {
+        Self::Send(value)
+    }
+}
+impl From<RecvError> for EmbassyError {
+    fn from(value: RecvError) -> Self {
+        Self::Recv(value)
+    }
+}
+impl From<ParseCOEError> for EmbassyError {
+    fn from(value: ParseCOEError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// A CoE endpoint on a bound embassy-net [UdpSocket].
+///
+/// ```rust,ignore
+/// static RX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+/// // ... the same for RX_BUFFER, TX_META and TX_BUFFER
+/// let mut socket = UdpSocket::new(stack, RX_META.init(..), RX_BUFFER.init(..), TX_META.init(..), TX_BUFFER.init(..));
+/// socket.bind(5442)?;
+/// let mut socket = EmbassySocket::new(socket);
+/// loop {
+///     let (packet, sender) = socket.receive().await?;
+///     // handle the packet
+/// }
+/// ```
+pub struct EmbassySocket<'a> {
+    socket: UdpSocket<'a>,
+    buf: [u8; MAX_PACKET_SIZE],
+}
+impl<'a> EmbassySocket<'a> {
+    /// Use `socket`, which should already be bound, for CoE.
+    ///
+    /// CMIs send from and listen on port 5442.
+    pub fn new(socket: UdpSocket<'a>) -> Self {
+        Self {
+            socket,
+            buf: [0; MAX_PACKET_SIZE],
+        }
+    }
+
+    /// The underlying socket.
+    pub fn socket(&self) -> &UdpSocket<'a> {
+        &self.socket
+    }
+
+    /// The underlying socket.
+    pub fn socket_mut(&mut self) -> &mut UdpSocket<'a> {
+        &mut self.socket
+    }
+
+    /// Return the underlying socket.
+    pub fn into_inner(self) -> UdpSocket<'a> {
+        self.socket
+    }
+
+    /// Send `packet` to `destination`, waiting until the transmit buffer has room for it.
+    pub async fn send(
+        &mut self,
+        packet: &Packet,
+        destination: impl Into<IpEndpoint>,
+    ) -> Result<(), EmbassyError> {
+        let length = packet
+            .try_serialize_into(&mut self.buf)
+            .expect("A packet always fits into MAX_PACKET_SIZE bytes");
+        self.socket
+            .send_to(&self.buf[..length], destination.into())
+            .await?;
+        Ok(())
+    }
+
+    /// Wait for the next datagram and parse it, returning the [Packet] and its sender.
+    ///
+    /// Invalid datagrams are consumed and returned as an error, so calling this again waits for
+    /// the next datagram.
+    pub async fn receive(&mut self) -> Result<(Packet, IpEndpoint), EmbassyError> {
+        let (length, meta) = self.socket.recv_from(&mut self.buf).await?;
+        let packet = Packet::try_from(&self.buf[..length])?;
+        Ok((packet, meta.endpoint))
+    }
+}
+
+// the loopback interface needs a heap
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, Payload};
+    use embassy_futures::{
+        block_on,
+        select::{select, Either},
+    };
+    use embassy_net::{
+        udp::PacketMetadata, Config, Ipv4Address, Ipv4Cidr, StackResources, StaticConfigV4,
+    };
+    use embassy_net_driver::{Capabilities, Driver, HardwareAddress, LinkState};
+    use std::{collections::VecDeque, task::Context, vec, vec::Vec};
+
+    /// A network interface which receives every frame it transmits.
+    #[derive(Default)]
+    struct Loopback(VecDeque<Vec<u8>>);
+
+    struct RxToken(Vec<u8>);
+    impl embassy_net_driver::RxToken for RxToken {
+        fn consume<R, F: FnOnce(&mut [u8]) -> R>(mut self, f: F) -> R {
+            f(&mut self.0)
+        }
+    }
+
+    struct TxToken<'a>(&'a mut VecDeque<Vec<u8>>);
+    impl embassy_net_driver::TxToken for TxToken<'_> {
+        fn consume<R, F: FnOnce(&mut [u8]) -> R>(self, len: usize, f: F) -> R {
+            let mut frame = vec![0; len];
+            let result = f(&mut frame);
+            self.0.push_back(frame);
+            result
+        }
+    }
+
+    impl Driver for Loopback {
+        type RxToken<'a> = RxToken;
+        type TxToken<'a> = TxToken<'a>;
+
+        fn receive(&mut self, cx: &mut Context) -> Option<(RxToken, TxToken<'_>)> {
+            // poll again soon instead of tracking wakers for frames we transmit ourselves
+            cx.waker().wake_by_ref();
+            let frame = self.0.pop_front()?;
+            Some((RxToken(frame), TxToken(&mut self.0)))
+        }
+
+        fn transmit(&mut self, _cx: &mut Context) -> Option<TxToken<'_>> {
+            Some(TxToken(&mut self.0))
+        }
+
+        fn link_state(&mut self, _cx: &mut Context) -> LinkState {
+            LinkState::Up
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            let mut capabilities = Capabilities::default();
+            capabilities.max_transmission_unit = 1500;
+            capabilities
+        }
+
+        fn hardware_address(&self) -> HardwareAddress {
+            HardwareAddress::Ip
+        }
+    }
+
+    #[test]
+    fn send_and_receive() {
+        let address = Ipv4Address::new(10, 0, 0, 1);
+        let config = Config::ipv4_static(StaticConfigV4 {
+            address: Ipv4Cidr::new(address, 24),
+            gateway: None,
+            dns_servers: Default::default(),
+        });
+        let mut resources = StackResources::<2>::new();
+        let (stack, mut runner) = embassy_net::new(Loopback::default(), config, &mut resources, 0);
+
+        let mut buffers = [[0_u8; 512]; 4];
+        let mut meta = [[PacketMetadata::EMPTY; 4]; 4];
+        let [m0, m1, m2, m3] = &mut meta;
+        let [b0, b1, b2, b3] = &mut buffers;
+        let mut cmi = UdpSocket::new(stack, m0, b0, m1, b1);
+        cmi.bind(5441).unwrap();
+        let mut cmi = EmbassySocket::new(cmi);
+        let mut device = UdpSocket::new(stack, m2, b2, m3, b3);
+        device.bind(5442).unwrap();
+        let mut device = EmbassySocket::new(device);
+
+        let packet = Packet::try_from_payloads(&[Payload::new(
+            58,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+        )])
+        .unwrap();
+        let exchange = async {
+            cmi.send(&packet, (address, 5442)).await.unwrap();
+            let received = device.receive().await.unwrap();
+            device
+                .socket()
+                .send_to(&[2, 0], (address, 5441))
+                .await
+                .unwrap();
+            let invalid = cmi.receive().await;
+            (received, invalid)
+        };
+        match block_on(select(runner.run(), exchange)) {
+            Either::First(_) => unreachable!(),
+            Either::Second(((received, sender), invalid)) => {
+                assert_eq!(received, packet);
+                assert_eq!(sender, (address, 5441).into());
+                assert_eq!(
+                    invalid,
+                    Err(EmbassyError::Parse(ParseCOEError::PacketBelowHeaderLength))
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Export received values into time series databases.
+//!
+//! Values can be written as [InfluxDB line protocol](https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/)
+//! or in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//! All writers use [core::fmt::Write], so they work without allocation.
+//!
+//! Influx measurements are named after the [Dimension] of the unit (e.g. `temperature`), with
+//! `digital` for [DigitalCOEValue](crate::DigitalCOEValue)s, `date` for [AnalogueCOEValue::Date]s
+//! and `other` for the remaining analogue units without a dimension. Every line is tagged with
+//! `source`, `node`, `pdo`, `unit_id` and `unit` and contains the fields `value` (as shown by
+//! TA-Hardware) and `raw` (the on-wire value). Dates are written as an ISO 8601 string `value`
+//! without `raw`, so that every field keeps a single type within its measurement.
+//!
+//! Prometheus samples are gauges named `coe_value`, labelled like the Influx lines plus `family`.
+//! [AnalogueCOEValue::Date]s have no numeric value and are skipped.
+//!
+//! A [ProcessImage] keeps the latest value of every signal, so that a Prometheus scrape can be
+//! answered at any time.
+
+use core::fmt::Write;
+
+use crate::{AnalogueCOEValue, COEValue, Packet, Payload};
+
+/// The name of the Influx measurement and Prometheus `family` label for `value`.
+fn family(value: &COEValue) -> &'static str {
+    match value {
+        COEValue::Digital(_) => "digital",
+        COEValue::Analogue(AnalogueCOEValue::Date(..)) => "date",
+        COEValue::Analogue(x) => x.dimension().map_or("other", |d| d.name()),
+    }
+}
+
+/// The symbol of the unit of `value`, empty for digital values.
+fn unit_symbol(value: &COEValue) -> &'static str {
+    match value {
+        COEValue::Analogue(x) => x.unit_symbol(),
+        COEValue::Digital(_) => "",
+    }
+}
+
+/// Writes into the inner writer, escaping Influx tag keys and values.
+struct InfluxTagEscape<'a, W>(&'a mut W);
+impl<W: Write> Write for InfluxTagEscape<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            if matches!(c, ',' | ' ' | '=' | '\\') {
+                self.0.write_char('\\')?;
+            };
+            self.0.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes into the inner writer, escaping Prometheus label values.
+struct PrometheusLabelEscape<'a, W>(&'a mut W);
+impl<W: Write> Write for PrometheusLabelEscape<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            match c {
+                '\\' => self.0.write_str("\\\\")?,
+                '"' => self.0.write_str("\\\"")?,
+                '\n' => self.0.write_str("\\n")?,
+                c => self.0.write_char(c)?,
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Write a [Payload] as one line of InfluxDB line protocol, including the trailing newline.
+///
+/// `source` is usually the address of the sending CMI. `timestamp` is in nanoseconds since the
+/// unix epoch. Without it, the database uses the time the line was received.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Payload, write_influx_line};
+/// let payload = Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)));
+/// let mut line = String::new();
+/// write_influx_line(&mut line, "192.168.1.123", &payload, Some(1718600000000000000)).unwrap();
+/// assert_eq!(
+///     line,
+///     "temperature,source=192.168.1.123,node=58,pdo=1,unit_id=1,unit=°C value=21.5,raw=215i 1718600000000000000\n"
+/// );
+/// ```
+pub fn write_influx_line<W: Write, S: core::fmt::Display + ?Sized>(
+    w: &mut W,
+    source: &S,
+    payload: &Payload,
+    timestamp: Option<u64>,
+) -> core::fmt::Result {
+    let value = payload.value();
+    write!(w, "{},source=", family(&value))?;
+    write!(InfluxTagEscape(w), "{source}")?;
+    write!(
+        w,
+        ",node={},pdo={},unit_id={}",
+        payload.node(),
+        payload.pdo_index(),
+        payload.unit_id()
+    )?;
+    let unit = unit_symbol(&value);
+    // Influx does not allow empty tag values
+    if !unit.is_empty() {
+        w.write_str(",unit=")?;
+        InfluxTagEscape(w).write_str(unit)?;
+    };
+    match value {
+        COEValue::Analogue(AnalogueCOEValue::Date(day, month, year)) => {
+            write!(w, " value=\"{year:04}-{month:02}-{day:02}\"")?;
+        }
+        COEValue::Analogue(x) => {
+            let scaled = x.scaled().expect("Only Date has no scaled value");
+            write!(w, " value={scaled},raw={}i", x.raw())?;
+        }
+        COEValue::Digital(x) => {
+            write!(w, " value={}", x.value())?;
+        }
+    };
+    if let Some(timestamp) = timestamp {
+        write!(w, " {timestamp}")?;
+    };
+    w.write_char('\n')
+}
+
+/// Write all [Payload]s of a [Packet] as InfluxDB line protocol, one line per [Payload].
+///
+/// See [write_influx_line] for the format.
+pub fn write_influx_packet<W: Write, S: core::fmt::Display + ?Sized>(
+    w: &mut W,
+    source: &S,
+    packet: &Packet,
+    timestamp: Option<u64>,
+) -> core::fmt::Result {
+    for payload in packet.iter() {
+        write_influx_line(w, source, payload, timestamp)?;
+    }
+    Ok(())
+}
+
+/// Write the `# HELP` and `# TYPE` lines for `coe_value`.
+///
+/// Call this once before writing samples with [write_prometheus_sample].
+pub fn write_prometheus_header<W: Write>(w: &mut W) -> core::fmt::Result {
+    w.write_str("# HELP coe_value Latest value received via CoE, as shown by TA-Hardware.\n")?;
+    w.write_str("# TYPE coe_value gauge\n")
+}
+
+/// Write a [Payload] as a Prometheus sample of the gauge `coe_value`.
+///
+/// Digital values are written as `0` or `1`. [AnalogueCOEValue::Date]s are skipped.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Payload, write_prometheus_sample};
+/// let payload = Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)));
+/// let mut sample = String::new();
+/// write_prometheus_sample(&mut sample, "192.168.1.123", &payload).unwrap();
+/// assert_eq!(
+///     sample,
+///     "coe_value{source=\"192.168.1.123\",node=\"58\",pdo=\"1\",unit_id=\"1\",unit=\"°C\",family=\"temperature\"} 21.5\n"
+/// );
+/// ```
+pub fn write_prometheus_sample<W: Write, S: core::fmt::Display + ?Sized>(
+    w: &mut W,
+    source: &S,
+    payload: &Payload,
+) -> core::fmt::Result {
+    let value = payload.value();
+    let sample = match value {
+        COEValue::Analogue(x) => match x.scaled() {
+            Some(x) => x,
+            None => return Ok(()),
+        },
+        COEValue::Digital(x) => f64::from(u8::from(x.value())),
+    };
+    w.write_str("coe_value{source=\"")?;
+    write!(PrometheusLabelEscape(w), "{source}")?;
+    write!(
+        w,
+        "\",node=\"{}\",pdo=\"{}\",unit_id=\"{}\",unit=\"",
+        payload.node(),
+        payload.pdo_index(),
+        payload.unit_id()
+    )?;
+    PrometheusLabelEscape(w).write_str(unit_symbol(&value))?;
+    writeln!(w, "\",family=\"{}\"}} {sample}", family(&value))
+}
+
+#[cfg(feature = "alloc")]
+pub use process_image::ProcessImage;
+
+#[cfg(feature = "alloc")]
+mod process_image {
+    use alloc::collections::BTreeMap;
+
+    use super::*;
+    use crate::Format;
+
+    /// The latest value of every signal received, per source.
+    ///
+    /// A signal is identified by its source, node, PDO index and [Format], because analogue
+    /// and digital outputs are numbered separately.
+    ///
+    /// This is available only on the `alloc` feature flag.
+    /// ```rust
+    /// # use coe::{AnalogueCOEValue, COEValue, Payload, ProcessImage};
+    /// let mut image = ProcessImage::new();
+    /// image.insert("cmi-1", Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::Watt(10))));
+    /// image.insert("cmi-1", Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::Watt(20))));
+    /// assert_eq!(image.len(), 1);
+    ///
+    /// let mut out = String::new();
+    /// image.write_prometheus(&mut out).unwrap();
+    /// assert!(out.ends_with("coe_value{source=\"cmi-1\",node=\"58\",pdo=\"1\",unit_id=\"69\",unit=\"W\",family=\"power\"} 20\n"));
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct ProcessImage<S> {
+        values: BTreeMap<(S, u8, u8, Format), Payload>,
+    }
+    impl<S> Default for ProcessImage<S> {
+        fn default() -> Self {
+            ProcessImage {
+                values: BTreeMap::new(),
+            }
+        }
+    }
+    impl<S: Ord + Clone + core::fmt::Display> ProcessImage<S> {
+        /// Create an empty process image.
+        pub fn new() -> ProcessImage<S> {
+            ProcessImage::default()
+        }
+
+        /// Store every [Payload] of `packet` as the latest value of its signal.
+        pub fn update(&mut self, source: S, packet: &Packet) {
+            for payload in packet.iter() {
+                self.insert(source.clone(), *payload);
+            }
+        }
+
+        /// Store `payload` as the latest value of its signal.
+        pub fn insert(&mut self, source: S, payload: Payload) {
+            self.values.insert(
+                (
+                    source,
+                    payload.node(),
+                    payload.pdo_index(),
+                    payload.format(),
+                ),
+                payload,
+            );
+        }
+
+        /// Get the latest value of a signal.
+        pub fn get(&self, source: &S, node: u8, pdo_index: u8, format: Format) -> Option<&Payload> {
+            self.values.get(&(source.clone(), node, pdo_index, format))
+        }
+
+        /// Iterate over the latest value of every signal, ordered by source, node, PDO index and
+        /// format.
+        pub fn iter(&self) -> impl Iterator<Item = (&S, &Payload)> {
+            self.values
+                .iter()
+                .map(|((source, _, _, _), payload)| (source, payload))
+        }
+
+        /// The number of signals in this image.
+        pub fn len(&self) -> usize {
+            self.values.len()
+        }
+
+        /// Returns true iff no signal was received yet.
+        pub fn is_empty(&self) -> bool {
+            self.values.is_empty()
+        }
+
+        /// Write every signal as InfluxDB line protocol. See [write_influx_line].
+        pub fn write_influx<W: Write>(
+            &self,
+            w: &mut W,
+            timestamp: Option<u64>,
+        ) -> core::fmt::Result {
+            for (source, payload) in self.iter() {
+                write_influx_line(w, source, payload, timestamp)?;
+            }
+            Ok(())
+        }
+
+        /// Write every signal in the Prometheus text format, including the header. See
+        /// [write_prometheus_sample].
+        pub fn write_prometheus<W: Write>(&self, w: &mut W) -> core::fmt::Result {
+            write_prometheus_header(w)?;
+            for (source, payload) in self.iter() {
+                write_prometheus_sample(w, source, payload)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::DigitalCOEValue;
+
+    #[test]
+    fn influx_digital_and_date() {
+        let mut out = String::new();
+        let packet = Packet::try_from_payloads(&[
+            Payload::new(3, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+            Payload::new(
+                3,
+                1,
+                COEValue::Analogue(AnalogueCOEValue::Date(17, 6, 2024)),
+            ),
+        ])
+        .unwrap();
+        write_influx_packet(&mut out, "cmi", &packet, None).unwrap();
+        assert_eq!(
+            out,
+            "digital,source=cmi,node=3,pdo=0,unit_id=43 value=true\n\
+             date,source=cmi,node=3,pdo=1,unit_id=62 value=\"2024-06-17\"\n"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let payload = Payload::new(1, 0, COEValue::Analogue(AnalogueCOEValue::Watt(-3)));
+        let mut out = String::new();
+        write_influx_line(&mut out, "a b,c=d", &payload, None).unwrap();
+        assert_eq!(
+            out,
+            "power,source=a\\ b\\,c\\=d,node=1,pdo=0,unit_id=69,unit=W value=-3,raw=-3i\n"
+        );
+        let mut out = String::new();
+        write_prometheus_sample(&mut out, "a\"b\\", &payload).unwrap();
+        assert_eq!(
+            out,
+            "coe_value{source=\"a\\\"b\\\\\",node=\"1\",pdo=\"0\",unit_id=\"69\",unit=\"W\",family=\"power\"} -3\n"
+        );
+    }
+
+    #[test]
+    fn prometheus_skips_dates() {
+        let payload = Payload::new(
+            3,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::Date(17, 6, 2024)),
+        );
+        let mut out = String::new();
+        write_prometheus_sample(&mut out, "cmi", &payload).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn process_image_keeps_formats_apart() {
+        let mut image = ProcessImage::new();
+        let packet = Packet::try_from_payloads(&[
+            Payload::new(3, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+            Payload::new(3, 0, COEValue::Analogue(AnalogueCOEValue::Watt(7))),
+        ])
+        .unwrap();
+        image.update("cmi", &packet);
+        image.update("other", &packet);
+        assert_eq!(image.len(), 4);
+        assert_eq!(
+            image
+                .get(&"cmi", 3, 0, crate::Format::Digital)
+                .map(|p| p.value()),
+            Some(COEValue::Digital(DigitalCOEValue::OnOff(true)))
+        );
+        let mut out = String::new();
+        image.write_prometheus(&mut out).unwrap();
+        assert_eq!(out.lines().count(), 2 + 4);
+    }
+}
@@ -0,0 +1,457 @@
+//! C interface to the CoE codec.
+//!
+//! The header `include/coe.h` is generated from this module with
+//! `cbindgen --config cbindgen.toml --output include/coe.h`.
+//! All functions validate their input exactly like the Rust API does, return a [CoeStatus] and
+//! write their results only on success.
+
+use core::ffi::c_char;
+
+use crate::{COEVersion, Format, Packet, ParseCOEError, Payload};
+
+/// The result of every function in this interface.
+///
+/// The values 1-12 mirror the variants of `ParseCOEError`.
+#[repr(C)]
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CoeStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// The node is not in 1-62.
+    NodeDisallowed = 1,
+    /// The PDO index is not in 0-63.
+    PdoIndexDisallowed = 2,
+    /// The unit does not exist for the given format.
+    FormatAndUnitIncompatible = 3,
+    /// The format is neither 0 (digital) nor 1 (analogue).
+    FormatUnknown = 4,
+    /// A value was not 4 bytes long.
+    ValueSize = 5,
+    /// A digital value was neither 0 nor 1.
+    ValueNotBool = 6,
+    /// The buffer is shorter then the 4 byte CoE header.
+    PacketBelowHeaderLength = 7,
+    /// The CoE version is not 2.0.
+    VersionNotImplemented = 8,
+    /// Packet size and payload count in the header are inconsistent.
+    PacketLengthInconsistent = 9,
+    /// The buffer length differs from the packet size in the header.
+    PacketSizeConflictsWithHeader = 10,
+    /// A payload was not 8 bytes long.
+    PayloadFrameLengthIncorrect = 11,
+    /// A date does not exist in the calendar.
+    DateInvalid = 12,
+    /// A pointer argument was NULL.
+    NullPointer = 100,
+    /// The output buffer is too small.
+    BufferTooSmall = 101,
+    /// The packet already contains 31 payloads, or claims to contain more.
+    PacketFull = 102,
+}
+impl From<&ParseCOEError> for CoeStatus {
+    fn from(value: &ParseCOEError) -> Self {
+        match value {
+            ParseCOEError::NodeDisallowed(_) => Self::NodeDisallowed,
+            ParseCOEError::PDOIndexDisallowed(_) => Self::PdoIndexDisallowed,
+            ParseCOEError::FormatAndUnitIncompatible(_, _) => Self::FormatAndUnitIncompatible,
+            ParseCOEError::FormatUnknown(_) => Self::FormatUnknown,
+            ParseCOEError::ValueSize(_) => Self::ValueSize,
+            ParseCOEError::ValueNotBool(_) => Self::ValueNotBool,
+            ParseCOEError::PacketBelowHeaderLength => Self::PacketBelowHeaderLength,
+            ParseCOEError::VersionNotImplemented(_, _) => Self::VersionNotImplemented,
+            ParseCOEError::PacketLengthInconsistent(_, _) => Self::PacketLengthInconsistent,
+            ParseCOEError::PacketSizeConflictsWithHeader(_, _) => {
+                Self::PacketSizeConflictsWithHeader
+            }
+            ParseCOEError::PayloadFrameLengthIncorrect(_) => Self::PayloadFrameLengthIncorrect,
+            ParseCOEError::DateInvalid(_) => Self::DateInvalid,
+        }
+    }
+}
+impl From<ParseCOEError> for CoeStatus {
+    fn from(value: ParseCOEError) -> Self {
+        (&value).into()
+    }
+}
+
+/// A payload in its on-wire representation.
+#[repr(C)]
+#[derive(Hash, Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct CoePayload {
+    /// The CAN-ID of the virtual node (1-62).
+    pub node: u8,
+    /// The output index (0-63), without the offset of 1 shown in the GUI.
+    pub pdo_index: u8,
+    /// 0 for digital and 1 for analogue values.
+    pub format: u8,
+    /// The unit ID.
+    pub unit_id: u8,
+    /// The unscaled value. Digital values are 0 or 1.
+    pub raw: i32,
+}
+impl From<&Payload> for CoePayload {
+    fn from(value: &Payload) -> Self {
+        let mut buf = [0_u8; 8];
+        value.serialize_into(&mut buf);
+        CoePayload {
+            node: buf[0],
+            pdo_index: buf[1],
+            format: buf[2],
+            unit_id: buf[3],
+            raw: i32::from_le_bytes(buf[4..8].try_into().expect("slice has length 4")),
+        }
+    }
+}
+impl TryFrom<&CoePayload> for Payload {
+    type Error = ParseCOEError;
+    fn try_from(value: &CoePayload) -> Result<Self, Self::Error> {
+        let mut buf = [0_u8; 8];
+        buf[0] = value.node;
+        buf[1] = value.pdo_index;
+        buf[2] = value.format;
+        buf[3] = value.unit_id;
+        buf[4..8].copy_from_slice(&value.raw.to_le_bytes());
+        Payload::try_from(&buf[..])
+    }
+}
+
+/// A packet with up to 31 payloads.
+#[repr(C)]
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+pub struct CoePacket {
+    /// The major CoE version, always 2.
+    pub version_major: u8,
+    /// The minor CoE version, always 0.
+    pub version_minor: u8,
+    /// The number of used entries in `payloads`.
+    pub length: u8,
+    /// The payloads. Only the first `length` entries are used.
+    pub payloads: [CoePayload; 31],
+}
+impl Default for CoePacket {
+    fn default() -> Self {
+        CoePacket {
+            version_major: 2,
+            version_minor: 0,
+            length: 0,
+            payloads: [CoePayload::default(); 31],
+        }
+    }
+}
+impl From<&Packet> for CoePacket {
+    fn from(value: &Packet) -> Self {
+        let mut res = CoePacket::default();
+        for (slot, payload) in res.payloads.iter_mut().zip(value.iter()) {
+            *slot = payload.into();
+        }
+        res.length = u8::try_from(value.len()).expect("Packets contain at most 31 payloads");
+        res
+    }
+}
+impl TryFrom<&CoePacket> for Packet {
+    type Error = CoeStatus;
+    fn try_from(value: &CoePacket) -> Result<Self, Self::Error> {
+        COEVersion::try_from((value.version_major, value.version_minor))?;
+        let payloads = value
+            .payloads
+            .get(..usize::from(value.length))
+            .ok_or(CoeStatus::PacketFull)?;
+        let mut packet = Packet::new();
+        for payload in payloads {
+            let payload = Payload::try_from(payload)?;
+            if let Err(e) = payload.check() {
+                return Err(ParseCOEError::DateInvalid(e).into());
+            };
+            packet
+                .try_push(payload)
+                .expect("At most 31 checked payloads are pushed");
+        }
+        Ok(packet)
+    }
+}
+
+/// Parse `length` bytes at `buf` into `out`.
+///
+/// # Safety
+///
+/// `buf` must point to `length` readable bytes and `out` to a writable [CoePacket].
+#[no_mangle]
+pub unsafe extern "C" fn coe_packet_parse(
+    buf: *const u8,
+    length: usize,
+    out: *mut CoePacket,
+) -> CoeStatus {
+    if buf.is_null() || out.is_null() {
+        return CoeStatus::NullPointer;
+    };
+    // SAFETY: the caller guarantees that buf points to length readable bytes.
+    let buf = unsafe { core::slice::from_raw_parts(buf, length) };
+    match Packet::try_from(buf) {
+        Ok(packet) => {
+            // SAFETY: the caller guarantees that out points to a writable CoePacket.
+            unsafe { out.write((&packet).into()) };
+            CoeStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Initialize `out` as a packet without payloads.
+///
+/// # Safety
+///
+/// `out` must point to a writable [CoePacket].
+#[no_mangle]
+pub unsafe extern "C" fn coe_packet_init(out: *mut CoePacket) -> CoeStatus {
+    if out.is_null() {
+        return CoeStatus::NullPointer;
+    };
+    // SAFETY: the caller guarantees that out points to a writable CoePacket.
+    unsafe { out.write(CoePacket::default()) };
+    CoeStatus::Ok
+}
+
+/// Build a payload from node, PDO index, unit ID and the unscaled value.
+///
+/// The format is derived from the unit, because every unit is either analogue or digital.
+///
+/// # Safety
+///
+/// `out` must point to a writable [CoePayload].
+#[no_mangle]
+pub unsafe extern "C" fn coe_payload_new(
+    node: u8,
+    pdo_index: u8,
+    unit_id: u8,
+    raw: i32,
+    out: *mut CoePayload,
+) -> CoeStatus {
+    if out.is_null() {
+        return CoeStatus::NullPointer;
+    };
+    let mut payload = CoePayload {
+        node,
+        pdo_index,
+        format: 1,
+        unit_id,
+        raw,
+    };
+    let parsed = match Payload::try_from(&payload) {
+        Err(ParseCOEError::FormatAndUnitIncompatible(Format::Analogue, _)) => {
+            payload.format = 0;
+            Payload::try_from(&payload)
+        }
+        x => x,
+    };
+    match parsed.and_then(|p| p.check().map_err(ParseCOEError::DateInvalid)) {
+        Ok(()) => {
+            // SAFETY: the caller guarantees that out points to a writable CoePayload.
+            unsafe { out.write(payload) };
+            CoeStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Append a copy of `payload` to `packet`, after validating it.
+///
+/// # Safety
+///
+/// `packet` must point to a valid, writable [CoePacket] and `payload` to a readable [CoePayload].
+#[no_mangle]
+pub unsafe extern "C" fn coe_packet_push(
+    packet: *mut CoePacket,
+    payload: *const CoePayload,
+) -> CoeStatus {
+    if packet.is_null() || payload.is_null() {
+        return CoeStatus::NullPointer;
+    };
+    // SAFETY: the caller guarantees that both pointers are valid.
+    let (packet, payload) = unsafe { (&mut *packet, &*payload) };
+    match Payload::try_from(payload) {
+        Ok(p) => {
+            if let Err(e) = p.check() {
+                return ParseCOEError::DateInvalid(e).into();
+            };
+        }
+        Err(e) => return e.into(),
+    };
+    match packet.payloads.get_mut(usize::from(packet.length)) {
+        Some(slot) => {
+            *slot = *payload;
+            packet.length += 1;
+            CoeStatus::Ok
+        }
+        None => CoeStatus::PacketFull,
+    }
+}
+
+/// Validate `packet` and serialize it into the `length` bytes at `buf`.
+///
+/// On success, the number of bytes written (at most 252) is stored in `written`.
+///
+/// # Safety
+///
+/// `packet` must point to a readable [CoePacket], `buf` to `length` writable bytes and `written`
+/// to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn coe_packet_serialize(
+    packet: *const CoePacket,
+    buf: *mut u8,
+    length: usize,
+    written: *mut usize,
+) -> CoeStatus {
+    if packet.is_null() || buf.is_null() || written.is_null() {
+        return CoeStatus::NullPointer;
+    };
+    // SAFETY: the caller guarantees that packet is readable.
+    let packet = match Packet::try_from(unsafe { &*packet }) {
+        Ok(x) => x,
+        Err(e) => return e,
+    };
+    // SAFETY: the caller guarantees that buf points to length writable bytes.
+    let buf = unsafe { core::slice::from_raw_parts_mut(buf, length) };
+    match packet.try_serialize_into(buf) {
+        Some(x) => {
+            // SAFETY: the caller guarantees that written is writable.
+            unsafe { written.write(x) };
+            CoeStatus::Ok
+        }
+        None => CoeStatus::BufferTooSmall,
+    }
+}
+
+/// A static, NUL-terminated English description of `status`, a [CoeStatus].
+///
+/// Takes the status as an integer, so that C callers may pass any value. Values that are not a
+/// [CoeStatus] get a generic description.
+#[no_mangle]
+pub extern "C" fn coe_status_message(status: i32) -> *const c_char {
+    match status {
+        0 => c"Success.",
+        1 => c"The node must be in 1-62.",
+        2 => c"The PDO index must be in 0-63.",
+        3 => c"The unit does not exist in this format.",
+        4 => c"The format is not known.",
+        5 => c"The value is not 4 bytes long.",
+        6 => c"The digital value is neither 0 nor 1.",
+        7 => c"The packet is not at least 4 bytes long.",
+        8 => c"The CoE version is not implemented.",
+        9 => c"The packet size and payload length are inconsistent.",
+        10 => c"The packet size conflicts with the header.",
+        11 => c"The payload is not 8 bytes long.",
+        12 => c"The date does not exist in the calendar.",
+        100 => c"A pointer argument was NULL.",
+        101 => c"The buffer is too small.",
+        102 => c"The packet cannot hold more then 31 payloads.",
+        _ => c"The status is not known.",
+    }
+    .as_ptr()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, DigitalCOEValue};
+    use core::ffi::CStr;
+
+    #[test]
+    fn build_and_parse() {
+        let mut packet = CoePacket::default();
+        let mut payload = CoePayload::default();
+        unsafe {
+            assert_eq!(coe_packet_init(&mut packet), CoeStatus::Ok);
+            assert_eq!(coe_payload_new(58, 1, 1, 215, &mut payload), CoeStatus::Ok);
+            assert_eq!(coe_packet_push(&mut packet, &payload), CoeStatus::Ok);
+            assert_eq!(coe_payload_new(58, 2, 43, 1, &mut payload), CoeStatus::Ok);
+            assert_eq!(payload.format, 0);
+            assert_eq!(coe_packet_push(&mut packet, &payload), CoeStatus::Ok);
+        }
+
+        let mut buf = [0_u8; 252];
+        let mut written = 0;
+        assert_eq!(
+            unsafe { coe_packet_serialize(&packet, buf.as_mut_ptr(), buf.len(), &mut written) },
+            CoeStatus::Ok
+        );
+        assert_eq!(written, 20);
+        assert_eq!(
+            Packet::try_from(&buf[..written]).unwrap(),
+            Packet::try_from_payloads(&[
+                Payload::new(
+                    58,
+                    1,
+                    COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215))
+                ),
+                Payload::new(58, 2, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+            ])
+            .unwrap()
+        );
+
+        let mut parsed = CoePacket::default();
+        assert_eq!(
+            unsafe { coe_packet_parse(buf.as_ptr(), written, &mut parsed) },
+            CoeStatus::Ok
+        );
+        assert_eq!(parsed, packet);
+    }
+
+    #[test]
+    fn errors() {
+        let mut payload = CoePayload::default();
+        let mut packet = CoePacket::default();
+        unsafe {
+            assert_eq!(
+                coe_payload_new(0, 1, 1, 215, &mut payload),
+                CoeStatus::NodeDisallowed
+            );
+            assert_eq!(
+                coe_payload_new(1, 64, 1, 215, &mut payload),
+                CoeStatus::PdoIndexDisallowed
+            );
+            assert_eq!(
+                coe_payload_new(1, 1, 43, 2, &mut payload),
+                CoeStatus::ValueNotBool
+            );
+            assert_eq!(
+                coe_payload_new(1, 1, 1, 215, core::ptr::null_mut()),
+                CoeStatus::NullPointer
+            );
+            assert_eq!(
+                coe_packet_parse([2, 0, 4].as_ptr(), 3, &mut packet),
+                CoeStatus::PacketBelowHeaderLength
+            );
+            assert_eq!(
+                coe_packet_parse([3, 0, 4, 0].as_ptr(), 4, &mut packet),
+                CoeStatus::VersionNotImplemented
+            );
+
+            assert_eq!(coe_payload_new(1, 1, 1, 215, &mut payload), CoeStatus::Ok);
+            for _ in 0..31 {
+                assert_eq!(coe_packet_push(&mut packet, &payload), CoeStatus::Ok);
+            }
+            assert_eq!(
+                coe_packet_push(&mut packet, &payload),
+                CoeStatus::PacketFull
+            );
+            let mut buf = [0_u8; 251];
+            let mut written = 0;
+            assert_eq!(
+                coe_packet_serialize(&packet, buf.as_mut_ptr(), buf.len(), &mut written),
+                CoeStatus::BufferTooSmall
+            );
+            packet.length = 32;
+            assert_eq!(
+                coe_packet_serialize(&packet, buf.as_mut_ptr(), buf.len(), &mut written),
+                CoeStatus::PacketFull
+            );
+            assert_eq!(written, 0);
+        }
+        let message = |status| unsafe { CStr::from_ptr(coe_status_message(status)) };
+        let unknown = message(42);
+        assert_eq!(unknown, c"The status is not known.");
+        for status in [CoeStatus::Ok, CoeStatus::DateInvalid, CoeStatus::PacketFull] {
+            assert_ne!(message(status as i32), unknown);
+        }
+    }
+}
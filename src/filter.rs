@@ -0,0 +1,556 @@
+//! Drop datagrams from unknown or flooding senders before parsing them.
+//!
+//! CoE has no authentication, so every host on the network can send packets to a listener. A
+//! [ReceiveFilter] accepts datagrams only from configured [Subnet]s and limits the packet rate of
+//! every sender. Both checks only look at the source address, so rejected datagrams are dropped
+//! without ever being parsed.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{net::IpAddr, str::FromStr, time::Duration};
+
+use crate::{Packet, ParseCOEError};
+
+/// The Errors that can occur when creating a [Subnet].
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SubnetError {
+    /// The part before the `/` is not an IP address.
+    InvalidAddress,
+    /// The prefix length is not a number or longer then the address.
+    InvalidPrefix,
+}
+impl core::fmt::Display for SubnetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::InvalidAddress => write!(f, "The subnet does not start with an IP address."),
+            Self::InvalidPrefix => write!(f, "The prefix length of the subnet is invalid."),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for SubnetError {}
+
+/// A range of IP addresses, given by an address and the length of the common prefix.
+///
+/// Parse subnets in CIDR notation, or single addresses:
+/// ```rust
+/// # use coe::Subnet;
+/// let subnet: Subnet = "192.168.1.0/24".parse().unwrap();
+/// assert!(subnet.contains("192.168.1.17".parse().unwrap()));
+/// assert!(!subnet.contains("192.168.2.17".parse().unwrap()));
+///
+/// let cmi: Subnet = "10.0.0.2".parse().unwrap();
+/// assert_eq!(cmi.prefix(), 32);
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Subnet {
+    address: IpAddr,
+    prefix: u8,
+}
+impl Subnet {
+    /// Create the subnet of all addresses sharing the first `prefix` bits with `address`.
+    ///
+    /// IPv4 addresses mapped into IPv6 are stored as the IPv4 address, shortening `prefix` by the
+    /// 96 bits of the mapping:
+    /// ```rust
+    /// # use coe::Subnet;
+    /// let subnet: Subnet = "::ffff:10.0.0.0/104".parse().unwrap();
+    /// assert_eq!(subnet, "10.0.0.0/8".parse().unwrap());
+    /// assert!(subnet.contains("10.1.2.3".parse().unwrap()));
+    /// ```
+    ///
+    /// Fails if `prefix` is longer than the address, i.e. 32 bits for IPv4 and 128 bits for IPv6.
+    pub fn new(address: IpAddr, prefix: u8) -> Result<Subnet, SubnetError> {
+        let max = if address.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(SubnetError::InvalidPrefix);
+        };
+        match (address, address.to_canonical()) {
+            (IpAddr::V6(_), IpAddr::V4(canonical)) if prefix >= 96 => Ok(Subnet {
+                address: IpAddr::V4(canonical),
+                prefix: prefix - 96,
+            }),
+            _ => Ok(Subnet { address, prefix }),
+        }
+    }
+
+    /// The subnet containing only `address`.
+    pub fn single(address: IpAddr) -> Subnet {
+        let address = address.to_canonical();
+        let prefix = if address.is_ipv4() { 32 } else { 128 };
+        Subnet { address, prefix }
+    }
+
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether `address` is in this subnet.
+    ///
+    /// IPv4 addresses mapped into IPv6 (as received on dual-stack sockets) are treated as the
+    /// IPv4 address.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.address, address.to_canonical()) {
+            (IpAddr::V4(subnet), IpAddr::V4(address)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(subnet) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(subnet), IpAddr::V6(address)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(subnet) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+impl From<IpAddr> for Subnet {
+    fn from(value: IpAddr) -> Self {
+        Self::single(value)
+    }
+}
+impl FromStr for Subnet {
+    type Err = SubnetError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let address: IpAddr = address.parse().map_err(|_| SubnetError::InvalidAddress)?;
+        match prefix {
+            Some(x) => Subnet::new(address, x.parse().map_err(|_| SubnetError::InvalidPrefix)?),
+            None => Ok(Subnet::single(address)),
+        }
+    }
+}
+impl core::fmt::Display for Subnet {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// The reasons for which a [ReceiveFilter] rejects a datagram.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FilterError {
+    /// The sender is not in any of the allowed subnets.
+    SourceNotAllowed(IpAddr),
+    /// The sender has exceeded its packet rate.
+    RateLimited(IpAddr),
+    /// The datagram passed the filter, but is not a valid CoE packet.
+    InvalidPacket(ParseCOEError),
+    /// The [rate limit](ReceiveFilter::rate_limit) allows no packets, or more than one packet
+    /// per nanosecond.
+    InvalidRateLimit,
+}
+impl core::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::SourceNotAllowed(x) => write!(f, "{x} is not allowed to send packets."),
+            Self::RateLimited(x) => write!(f, "{x} has exceeded its packet rate."),
+            Self::InvalidPacket(e) => write!(f, "Received an invalid packet: {e}"),
+            Self::InvalidRateLimit => write!(
+                f,
+                "The rate limit must allow between one packet per period and one per nanosecond."
+            ),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FilterError {}
+impl From<ParseCOEError> for FilterError {
+    fn from(value: ParseCOEError) -> Self {
+        Self::InvalidPacket(value)
+    }
+}
+
+/// Counters kept by a [ReceiveFilter].
+#[derive(Hash, Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FilterStats {
+    /// Datagrams that passed the filter.
+    pub accepted: u64,
+    /// Datagrams rejected because their sender is not allowed.
+    pub not_allowed: u64,
+    /// Datagrams rejected because their sender exceeded its packet rate.
+    pub rate_limited: u64,
+    /// Datagrams that passed the filter, but were not valid CoE [Packet]s.
+    pub invalid_packets: u64,
+}
+
+/// A packet rate, enforced with the generic cell rate algorithm.
+#[derive(Debug, Clone)]
+struct RateLimit {
+    /// The time between two packets at the sustained rate.
+    interval: Duration,
+    /// How far ahead of the sustained rate a sender may get, i.e. the burst size.
+    tolerance: Duration,
+}
+
+/// Accepts datagrams only from allowed senders and limits the packet rate of every sender.
+///
+/// Without any [allowed](ReceiveFilter::allow) subnet, all senders are allowed. Without a
+/// [rate limit](ReceiveFilter::rate_limit), senders may send as fast as they want.
+///
+/// Check the source of every datagram before parsing it:
+/// ```rust
+/// # use coe::{FilterError, Packet, ReceiveFilter};
+/// # use core::time::Duration;
+/// let mut filter = ReceiveFilter::new()
+///     .allow("192.168.1.0/24".parse().unwrap())
+///     .rate_limit(2, Duration::from_secs(1))
+///     .unwrap();
+/// let cmi = "192.168.1.2".parse().unwrap();
+/// let datagram = [2, 0, 4, 0];
+///
+/// let now = Duration::ZERO;
+/// assert_eq!(filter.parse_at(cmi, &datagram, now), Ok(Packet::new()));
+/// assert_eq!(filter.parse_at(cmi, &datagram, now), Ok(Packet::new()));
+/// // the third packet within the same second is too much
+/// assert_eq!(filter.parse_at(cmi, &datagram, now), Err(FilterError::RateLimited(cmi)));
+///
+/// let attacker = "10.0.0.66".parse().unwrap();
+/// assert_eq!(
+///     filter.check_at(attacker, now),
+///     Err(FilterError::SourceNotAllowed(attacker))
+/// );
+/// assert_eq!(filter.stats().accepted, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReceiveFilter {
+    allowed: Vec<Subnet>,
+    rate_limit: Option<RateLimit>,
+    max_sources: usize,
+    /// The theoretical arrival time of the next packet of every sender.
+    next_arrival: BTreeMap<IpAddr, Duration>,
+    /// The same entries as `next_arrival`, ordered by arrival time, to find idle senders quickly.
+    by_arrival: BTreeSet<(Duration, IpAddr)>,
+    stats: FilterStats,
+    #[cfg(feature = "std")]
+    started: std::time::Instant,
+}
+impl Default for ReceiveFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ReceiveFilter {
+    /// Create a filter accepting every datagram.
+    pub fn new() -> ReceiveFilter {
+        ReceiveFilter {
+            allowed: Vec::new(),
+            rate_limit: None,
+            max_sources: 1024,
+            next_arrival: BTreeMap::new(),
+            by_arrival: BTreeSet::new(),
+            stats: FilterStats::default(),
+            #[cfg(feature = "std")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// Accept datagrams from `subnet`.
+    ///
+    /// Call this multiple times to allow multiple subnets. Once a subnet is allowed, datagrams
+    /// from all other senders are rejected.
+    pub fn allow(mut self, subnet: Subnet) -> ReceiveFilter {
+        self.allowed.push(subnet);
+        self
+    }
+
+    /// Accept at most `packets` datagrams per `period` from every sender.
+    ///
+    /// Senders may send all `packets` at once, but then have to wait for the rate to catch up.
+    ///
+    /// Fails with [FilterError::InvalidRateLimit] if `packets` is 0, or `period / packets` is
+    /// shorter then a nanosecond.
+    pub fn rate_limit(
+        mut self,
+        packets: u32,
+        period: Duration,
+    ) -> Result<ReceiveFilter, FilterError> {
+        let interval = period
+            .checked_div(packets)
+            .filter(|x| !x.is_zero())
+            .ok_or(FilterError::InvalidRateLimit)?;
+        self.rate_limit = Some(RateLimit {
+            interval,
+            tolerance: period - interval,
+        });
+        Ok(self)
+    }
+
+    /// Track the rate of at most `max_sources` senders at once (1024 by default).
+    ///
+    /// The sender that has been idle the longest is forgotten when this limit is reached. Datagrams
+    /// from new senders are rate limited while all tracked senders are still active, so that
+    /// floods with spoofed source addresses cannot exhaust memory.
+    pub fn max_sources(mut self, max_sources: usize) -> ReceiveFilter {
+        self.max_sources = max_sources;
+        self
+    }
+
+    /// The subnets datagrams are accepted from. Empty if all senders are allowed.
+    pub fn allowed(&self) -> &[Subnet] {
+        &self.allowed
+    }
+
+    /// The counters of this filter.
+    pub fn stats(&self) -> FilterStats {
+        self.stats
+    }
+
+    /// Check whether a datagram from `source` received at `now` passes the filter.
+    ///
+    /// `now` is the time since any fixed point in the past, e.g. the start of the program. It
+    /// must not decrease between calls.
+    pub fn check_at(&mut self, source: IpAddr, now: Duration) -> Result<(), FilterError> {
+        let source = source.to_canonical();
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|x| x.contains(source)) {
+            self.stats.not_allowed += 1;
+            return Err(FilterError::SourceNotAllowed(source));
+        };
+        if let Some(rate_limit) = &self.rate_limit {
+            if !self.next_arrival.contains_key(&source)
+                && self.next_arrival.len() >= self.max_sources
+            {
+                // forget the sender idle for the longest time, if it is back at its sustained rate
+                if let Some(&(next, idle)) = self.by_arrival.first() {
+                    if next <= now {
+                        self.by_arrival.remove(&(next, idle));
+                        self.next_arrival.remove(&idle);
+                    };
+                };
+            };
+            let next = match self.next_arrival.get(&source) {
+                Some(next) => *next.max(&now),
+                None if self.next_arrival.len() < self.max_sources => now,
+                None => {
+                    self.stats.rate_limited += 1;
+                    return Err(FilterError::RateLimited(source));
+                }
+            };
+            if next - now > rate_limit.tolerance {
+                self.stats.rate_limited += 1;
+                return Err(FilterError::RateLimited(source));
+            };
+            if let Some(previous) = self.next_arrival.insert(source, next + rate_limit.interval) {
+                self.by_arrival.remove(&(previous, source));
+            };
+            self.by_arrival.insert((next + rate_limit.interval, source));
+        };
+        self.stats.accepted += 1;
+        Ok(())
+    }
+
+    /// Check `datagram` from `source` received at `now` and parse it only if it passes the
+    /// filter.
+    ///
+    /// See [ReceiveFilter::check_at] for `now`.
+    pub fn parse_at(
+        &mut self,
+        source: IpAddr,
+        datagram: &[u8],
+        now: Duration,
+    ) -> Result<Packet, FilterError> {
+        self.check_at(source, now)?;
+        Packet::try_from(datagram).map_err(|e| {
+            self.stats.invalid_packets += 1;
+            e.into()
+        })
+    }
+
+    /// Check whether a datagram from `source` received now passes the filter.
+    #[cfg(feature = "std")]
+    pub fn check(&mut self, source: IpAddr) -> Result<(), FilterError> {
+        self.check_at(source, self.started.elapsed())
+    }
+
+    /// Check `datagram` from `source` received now and parse it only if it passes the filter.
+    ///
+    /// ```rust,no_run
+    /// # use coe::ReceiveFilter;
+    /// # use std::net::UdpSocket;
+    /// let mut filter = ReceiveFilter::new().allow("192.168.1.2".parse().unwrap());
+    /// let socket = UdpSocket::bind("0.0.0.0:5442").unwrap();
+    /// let mut buf = [0_u8; 256];
+    /// loop {
+    ///     let (length, source) = socket.recv_from(&mut buf).unwrap();
+    ///     if let Ok(packet) = filter.parse(source.ip(), &buf[..length]) {
+    ///         // handle the packet
+    ///     };
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse(&mut self, source: IpAddr, datagram: &[u8]) -> Result<Packet, FilterError> {
+        self.parse_at(source, datagram, self.started.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn ip(x: &str) -> IpAddr {
+        x.parse().unwrap()
+    }
+
+    fn ms(x: u64) -> Duration {
+        Duration::from_millis(x)
+    }
+
+    #[test]
+    fn subnets() {
+        let subnet: Subnet = "10.1.0.0/16".parse().unwrap();
+        assert!(subnet.contains(ip("10.1.255.3")));
+        assert!(subnet.contains(ip("::ffff:10.1.0.1")));
+        assert!(!subnet.contains(ip("10.2.0.1")));
+        assert!(!subnet.contains(ip("fe80::1")));
+        assert_eq!(subnet.to_string(), "10.1.0.0/16");
+
+        let all: Subnet = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains(ip("255.255.255.255")));
+        let link_local: Subnet = "fe80::/10".parse().unwrap();
+        assert!(link_local.contains(ip("fe80::1234")));
+        assert!(!link_local.contains(ip("fec0::1")));
+        assert!(Subnet::single(ip("::1")).contains(ip("::1")));
+
+        assert_eq!(
+            "10.0.0.0/33".parse::<Subnet>(),
+            Err(SubnetError::InvalidPrefix)
+        );
+        assert_eq!(
+            "10.0.0.0/x".parse::<Subnet>(),
+            Err(SubnetError::InvalidPrefix)
+        );
+        assert_eq!("cmi/8".parse::<Subnet>(), Err(SubnetError::InvalidAddress));
+        assert!("::/128".parse::<Subnet>().is_ok());
+
+        // IPv4-mapped subnets match like their IPv4 address
+        let mapped: Subnet = "::ffff:10.0.0.0/104".parse().unwrap();
+        assert_eq!(mapped.to_string(), "10.0.0.0/8");
+        assert!(mapped.contains(ip("10.1.2.3")));
+        assert!(mapped.contains(ip("::ffff:10.1.2.3")));
+        assert!(Subnet::single(ip("::ffff:10.0.0.2")).contains(ip("10.0.0.2")));
+        // a prefix shorter than the mapping spans more than IPv4 addresses
+        let wide: Subnet = "::ffff:10.0.0.0/80".parse().unwrap();
+        assert_eq!(wide.prefix(), 80);
+    }
+
+    #[test]
+    fn allowlist() {
+        let mut filter = ReceiveFilter::new()
+            .allow("192.168.1.2".parse().unwrap())
+            .allow("10.0.0.0/8".parse().unwrap());
+        assert_eq!(filter.check_at(ip("192.168.1.2"), ms(0)), Ok(()));
+        assert_eq!(filter.check_at(ip("10.20.30.40"), ms(0)), Ok(()));
+        assert_eq!(
+            filter.check_at(ip("192.168.1.3"), ms(0)),
+            Err(FilterError::SourceNotAllowed(ip("192.168.1.3")))
+        );
+        assert_eq!(
+            filter.parse_at(ip("10.0.0.1"), &[2, 0], ms(0)),
+            Err(FilterError::InvalidPacket(
+                ParseCOEError::PacketBelowHeaderLength
+            ))
+        );
+        assert_eq!(
+            filter.stats(),
+            FilterStats {
+                accepted: 3,
+                not_allowed: 1,
+                rate_limited: 0,
+                invalid_packets: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn rate_limit() {
+        // a burst of 5 packets, then one every 200ms
+        let mut filter = ReceiveFilter::new()
+            .rate_limit(5, Duration::from_secs(1))
+            .unwrap();
+        let cmi = ip("10.0.0.2");
+        for _ in 0..5 {
+            assert_eq!(filter.check_at(cmi, ms(0)), Ok(()));
+        }
+        assert_eq!(
+            filter.check_at(cmi, ms(100)),
+            Err(FilterError::RateLimited(cmi))
+        );
+        // other senders have their own rate
+        assert_eq!(filter.check_at(ip("10.0.0.3"), ms(100)), Ok(()));
+        assert_eq!(filter.check_at(cmi, ms(200)), Ok(()));
+        assert_eq!(
+            filter.check_at(cmi, ms(300)),
+            Err(FilterError::RateLimited(cmi))
+        );
+        for t in (400..2000).step_by(200) {
+            assert_eq!(filter.check_at(cmi, ms(t)), Ok(()));
+        }
+        // after being quiet, the full burst is available again
+        for _ in 0..5 {
+            assert_eq!(filter.check_at(cmi, ms(5000)), Ok(()));
+        }
+        assert_eq!(filter.stats().rate_limited, 2);
+    }
+
+    #[test]
+    fn spoofed_sources() {
+        let mut filter = ReceiveFilter::new()
+            .rate_limit(1, Duration::from_secs(1))
+            .unwrap()
+            .max_sources(2);
+        assert_eq!(filter.check_at(ip("10.0.0.1"), ms(0)), Ok(()));
+        assert_eq!(filter.check_at(ip("10.0.0.2"), ms(0)), Ok(()));
+        assert_eq!(
+            filter.check_at(ip("10.0.0.3"), ms(500)),
+            Err(FilterError::RateLimited(ip("10.0.0.3")))
+        );
+        // once a tracked sender is idle, it is forgotten to make room
+        assert_eq!(filter.check_at(ip("10.0.0.3"), ms(1000)), Ok(()));
+        assert_eq!(filter.next_arrival.len(), 2);
+        assert_eq!(filter.by_arrival.len(), 2);
+        assert_eq!(filter.check_at(ip("10.0.0.4"), ms(1000)), Ok(()));
+        assert_eq!(
+            filter.next_arrival.keys().copied().collect::<Vec<_>>(),
+            [ip("10.0.0.3"), ip("10.0.0.4")]
+        );
+        // active senders are kept
+        assert_eq!(filter.check_at(ip("10.0.0.3"), ms(2000)), Ok(()));
+        assert_eq!(filter.check_at(ip("10.0.0.4"), ms(2000)), Ok(()));
+        assert_eq!(
+            filter.check_at(ip("10.0.0.5"), ms(2500)),
+            Err(FilterError::RateLimited(ip("10.0.0.5")))
+        );
+    }
+
+    #[test]
+    fn invalid_rate_limit() {
+        assert_eq!(
+            ReceiveFilter::new()
+                .rate_limit(10, Duration::from_nanos(9))
+                .map(|_| ()),
+            Err(FilterError::InvalidRateLimit)
+        );
+        assert_eq!(
+            ReceiveFilter::new()
+                .rate_limit(0, Duration::from_secs(1))
+                .map(|_| ()),
+            Err(FilterError::InvalidRateLimit)
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Building blocks shared by the `arbitrary` and `proptest` implementations.
+
+/// The IDs of all units of [AnalogueCOEValue](crate::AnalogueCOEValue).
+pub(crate) const ANALOGUE_UNIT_IDS: &[u8] = crate::AnalogueCOEValue::UNIT_IDS;
+
+/// The IDs of all units of [DigitalCOEValue](crate::DigitalCOEValue).
+pub(crate) const DIGITAL_UNIT_IDS: &[u8] = crate::DigitalCOEValue::UNIT_IDS;
+
+/// The unit ID of [AnalogueCOEValue::Date](crate::AnalogueCOEValue::Date), which cannot be
+/// generated from an arbitrary raw value.
+pub(crate) use crate::date::DATE_UNIT_ID;
@@ -0,0 +1,212 @@
+//! Conversions between the date and time units and [jiff].
+//!
+//! A [Date] converts into [AnalogueCOEValue::Date], or into [DayOfMonth] and [MonthOfYear]
+//! for [AnalogueCOEValue::DayOfMonth] and [AnalogueCOEValue::MonthOfYear]. Only
+//! [AnalogueCOEValue::Date] and [AnalogueCOEValue::Time] carry enough information to be
+//! converted back into a [Date] or [Time].
+
+use ::jiff::civil::{Date, Time};
+
+use crate::{AnalogueCOEValue, DateTimeConversionError, DayOfMonth, MonthOfYear};
+
+/// Convert a [Date] into [AnalogueCOEValue::Date].
+///
+/// Fails if the year is negative.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use jiff::civil::date;
+/// let val = AnalogueCOEValue::try_from(date(2024, 2, 29));
+/// assert_eq!(val, Ok(AnalogueCOEValue::Date(29, 2, 2024)));
+/// ```
+impl TryFrom<Date> for AnalogueCOEValue {
+    type Error = DateTimeConversionError;
+    fn try_from(value: Date) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year().into()))?;
+        Ok(AnalogueCOEValue::Date(
+            value.day().try_into().expect("Day of month is in 1..=31"),
+            value.month().try_into().expect("Month is in 1..=12"),
+            year,
+        ))
+    }
+}
+
+/// Convert a [Date] into its [DayOfMonth], dropping the year.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DayOfMonth};
+/// use jiff::civil::date;
+/// let val = AnalogueCOEValue::from(DayOfMonth::from(date(2024, 6, 17)));
+/// assert_eq!(val, AnalogueCOEValue::DayOfMonth(171));
+/// ```
+impl From<Date> for DayOfMonth {
+    fn from(value: Date) -> Self {
+        DayOfMonth::new(
+            value.day().try_into().expect("Day of month is in 1..=31"),
+            value.month().try_into().expect("Month is in 1..=12"),
+        )
+        .expect("A Date exists in the calendar")
+    }
+}
+
+/// Convert a [Date] into its [MonthOfYear], dropping the day.
+///
+/// Fails if the year is negative.
+impl TryFrom<Date> for MonthOfYear {
+    type Error = DateTimeConversionError;
+    fn try_from(value: Date) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year().into()))?;
+        Ok(
+            MonthOfYear::new(value.month().try_into().expect("Month is in 1..=12"), year)
+                .expect("Month is in 1..=12"),
+        )
+    }
+}
+
+/// Convert [AnalogueCOEValue::Date] into a [Date].
+///
+/// Fails for other units, for dates which do not exist in the calendar and for years after 9999,
+/// which [jiff] cannot represent.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use jiff::civil::{date, Date};
+/// let val = Date::try_from(AnalogueCOEValue::Date(17, 6, 2024));
+/// assert_eq!(val, Ok(date(2024, 6, 17)));
+///
+/// let val = Date::try_from(AnalogueCOEValue::Date(30, 2, 2024));
+/// assert_eq!(val, Err(DateTimeConversionError::InvalidDate(30, 2, 2024)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for Date {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Date(days, months, years) => {
+                let year = i16::try_from(years)
+                    .ok()
+                    .filter(|y| *y <= 9999)
+                    .ok_or(DateTimeConversionError::YearOutOfRange(years.into()))?;
+                let invalid = DateTimeConversionError::InvalidDate(days, months, years);
+                Date::new(
+                    year,
+                    i8::try_from(months).map_err(|_| invalid)?,
+                    i8::try_from(days).map_err(|_| invalid)?,
+                )
+                .map_err(|_| invalid)
+            }
+            _ => Err(DateTimeConversionError::NotDate),
+        }
+    }
+}
+
+/// Convert a [Time] into [AnalogueCOEValue::Time].
+///
+/// CoE only transmits hours and minutes, seconds are truncated.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use jiff::civil::time;
+/// let val = AnalogueCOEValue::from(time(13, 37, 59, 0));
+/// assert_eq!(val, AnalogueCOEValue::Time(13 * 60 + 37));
+/// ```
+impl From<Time> for AnalogueCOEValue {
+    fn from(value: Time) -> Self {
+        AnalogueCOEValue::Time(i32::from(value.hour()) * 60 + i32::from(value.minute()))
+    }
+}
+
+/// Convert [AnalogueCOEValue::Time] into a [Time].
+///
+/// Fails for other units and for values outside of 00:00-23:59.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use jiff::civil::{time, Time};
+/// let val = Time::try_from(AnalogueCOEValue::Time(13 * 60 + 37));
+/// assert_eq!(val, Ok(time(13, 37, 0, 0)));
+///
+/// let val = Time::try_from(AnalogueCOEValue::Time(24 * 60));
+/// assert_eq!(val, Err(DateTimeConversionError::InvalidTime(24 * 60)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for Time {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Time(x) => {
+                if !(0..24 * 60).contains(&x) {
+                    return Err(DateTimeConversionError::InvalidTime(x));
+                };
+                Time::new(
+                    (x / 60).try_into().expect("x / 60 is in 0..24"),
+                    (x % 60).try_into().expect("Modulo 60 yields i8"),
+                    0,
+                    0,
+                )
+                .map_err(|_| DateTimeConversionError::InvalidTime(x))
+            }
+            _ => Err(DateTimeConversionError::NotTime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::jiff::civil::{date, time};
+
+    #[test]
+    fn date_roundtrip() {
+        let val = AnalogueCOEValue::try_from(date(1999, 12, 31)).unwrap();
+        assert_eq!(val, AnalogueCOEValue::Date(31, 12, 1999));
+        assert_eq!(Date::try_from(val), Ok(date(1999, 12, 31)));
+    }
+
+    #[test]
+    fn date_year_out_of_range() {
+        assert_eq!(
+            Date::try_from(AnalogueCOEValue::Date(1, 1, 10000)),
+            Err(DateTimeConversionError::YearOutOfRange(10000))
+        );
+        assert_eq!(
+            AnalogueCOEValue::try_from(date(-5, 1, 1)),
+            Err(DateTimeConversionError::YearOutOfRange(-5))
+        );
+    }
+
+    #[test]
+    fn date_rejects_invalid_day() {
+        assert_eq!(
+            Date::try_from(AnalogueCOEValue::Date(0, 2, 2023)),
+            Err(DateTimeConversionError::InvalidDate(0, 2, 2023))
+        );
+        assert_eq!(
+            Date::try_from(AnalogueCOEValue::Date(200, 2, 2023)),
+            Err(DateTimeConversionError::InvalidDate(200, 2, 2023))
+        );
+    }
+
+    #[test]
+    fn day_and_month() {
+        assert_eq!(
+            DayOfMonth::from(date(2024, 2, 29)),
+            DayOfMonth::new(29, 2).unwrap()
+        );
+        assert_eq!(
+            MonthOfYear::try_from(date(2024, 2, 29)),
+            Ok(MonthOfYear::new(2, 2024).unwrap())
+        );
+        assert_eq!(
+            MonthOfYear::try_from(date(-1, 1, 1)),
+            Err(DateTimeConversionError::YearOutOfRange(-1))
+        );
+    }
+
+    #[test]
+    fn time_bounds() {
+        assert_eq!(
+            Time::try_from(AnalogueCOEValue::Time(-1)),
+            Err(DateTimeConversionError::InvalidTime(-1))
+        );
+        assert_eq!(
+            Time::try_from(AnalogueCOEValue::Time(24 * 60 - 1)),
+            Ok(time(23, 59, 0, 0))
+        );
+    }
+}
@@ -25,20 +25,44 @@
 //! # Feature Flags
 //! The following feature flags are available:
 //! - `std`: This is the default feature set.
-//! - `serde`: This makes Packets, Paylods and Values Serializable with Serde.
+//! - `serde`: This makes Packets, Paylods and Values Serializable with Serde. Deserialization is
+//!   validated like parsing from bytes. Wrap Packets and Payloads in `Readable` for a
+//!   human-friendly representation with scaled values and unit symbols.
+//! - `schemars`: Generate JSON Schemas for the serde representations, including the bounds of
+//!   node and PDO index.
+//! - `chrono`, `time`, `jiff`: Conversions between [AnalogueCOEValue::Date] and
+//!   [AnalogueCOEValue::Time] and the date and time types of the respective crate.
+//! - `uom`: Conversions between analogue values and the quantities of `uom`, e.g.
+//!   `ThermodynamicTemperature` or `Power`.
+//! - `arbitrary`, `proptest`: Generate random, but always valid [Packet]s, [Payload]s and values
+//!   for fuzzing and property testing.
+//! - `sqlite`: Record received values into SQLite with `Recorder` and query their history.
+//! - `ffi`: A C interface in the [ffi] module, with the header `include/coe.h`.
+//! - `wasm`: wasm-bindgen exports for decoding and encoding [Packet]s in the browser, see the
+//!   [wasm] module.
+//! - `tunnel`: Forward [Packet]s between sites inside an authenticated, encrypted envelope
+//!   (XChaCha20-Poly1305 with a pre-shared key) with `TunnelSender` and `TunnelReceiver`.
+//! - `batch`: Send and receive many [Packet]s per syscall with `BatchSocket`, using `sendmmsg` and
+//!   `recvmmsg` on Linux.
+//! - `embassy`: Send and receive [Packet]s on an embassy-net `UdpSocket` with `EmbassySocket`.
+//!   This does not need `alloc`.
+//! - `smoltcp`: Drain received [Packet]s from a smoltcp UDP socket and enqueue outgoing ones in a
+//!   bare-metal poll loop with `receive_packets` and `send_packet`. This does not need `alloc`.
+//! - `defmt`: Implement `defmt::Format` for all public types that are available without `std`, so
+//!   firmware can log [Packet]s and errors without the cost of [core::fmt].
 //!
 //! You can further opt-out of the default features with `default-features = false` your dependency listing for coe.
 //! This makes `coe` depend only on [core], for use in no_alloc / no_std environments.
 //! You can reenable the following feature flags
 //! - `alloc`: This switches the implementation for a Packet from a fixed-size buffer to a Vec,
-//!    which is usually more memory-efficient. It also enables the [packets_from_payloads]
-//!    function.
+//!   which is usually more memory-efficient. It also enables the [packets_from_payloads]
+//!   function.
 //! - Going from `alloc` to `std` implements [std::error::Error] on all Error types.
 //!
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(feature = "alloc")]
+#[cfg(any(feature = "alloc", feature = "schemars"))]
 extern crate alloc;
 
 #[cfg(feature = "alloc")]
@@ -48,10 +72,12 @@ use alloc::{vec, vec::Vec};
 mod tests;
 
 mod packet_common;
+pub use packet_common::PayloadMut;
 
 /// The Format a COE Value can have.
-#[derive(Hash, Debug, PartialEq, Eq, Clone)]
+#[derive(Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Format {
     Analogue,
     Digital,
@@ -60,6 +86,7 @@ pub enum Format {
 /// All the Errors that can appear when parsing a COE packet.
 #[derive(Hash, Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ParseCOEError {
     /// The Node value is not allowed (1-62)
     NodeDisallowed(u8),
@@ -85,6 +112,8 @@ pub enum ParseCOEError {
     PacketSizeConflictsWithHeader(u8, usize),
     /// Got a payload frame that is not 8 bytes long.
     PayloadFrameLengthIncorrect(usize),
+    /// Got a [AnalogueCOEValue::Date] that does not exist in the calendar.
+    DateInvalid(DateError),
 }
 impl core::fmt::Display for ParseCOEError {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -145,12 +174,120 @@ impl core::fmt::Display for ParseCOEError {
             Self::PayloadFrameLengthIncorrect(actual) => {
                 write!(f, "Got a payload frame of length {actual}. 8 expected.")
             }
+            Self::DateInvalid(e) => {
+                write!(f, "Got an invalid Date: {e}")
+            }
         }
     }
 }
 #[cfg(feature = "std")]
 impl std::error::Error for ParseCOEError {}
 
+mod date;
+#[cfg(any(feature = "chrono", feature = "time", feature = "jiff"))]
+pub use date::DateTimeConversionError;
+pub use date::{
+    days_in_month, from_date, from_day_of_month, from_month_of_year, is_leap_year, to_date,
+    to_day_of_month, to_month_of_year, validate_date, DateError, DayOfMonth, FromDateError,
+    FromDayOfMonthError, FromMonthOfYearError, MonthOfYear,
+};
+
+mod units;
+pub use units::{ConvertUnitError, Dimension};
+
+mod time_of_day;
+pub use time_of_day::{from_time, to_colon, to_time, FromTimeError, TimeError, TimeOfDay};
+
+mod export;
+#[cfg(feature = "alloc")]
+pub use export::ProcessImage;
+pub use export::{
+    write_influx_line, write_influx_packet, write_prometheus_header, write_prometheus_sample,
+};
+
+mod codes;
+pub use codes::{
+    Blinds, BlindsPosition, CodedValueError, HeatingCircuitOpLevel, HeatingCircuitOpMode,
+    MixerMode, RasMode, UnknownCode,
+};
+
+mod builder;
+pub use builder::PacketBuilder;
+
+#[cfg(feature = "alloc")]
+mod relay;
+#[cfg(feature = "alloc")]
+pub use relay::{Relay, RelayStats, Rule, Target};
+
+#[cfg(feature = "alloc")]
+mod filter;
+#[cfg(feature = "alloc")]
+pub use filter::{FilterError, FilterStats, ReceiveFilter, Subnet, SubnetError};
+
+#[cfg(feature = "std")]
+mod capture;
+#[cfg(feature = "std")]
+pub use capture::{
+    record_capture, replay_capture, CaptureError, CaptureReader, CaptureRecord, CaptureWriter,
+};
+
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+// the exports generated by wasm-bindgen contain unsafe code
+#[allow(unsafe_code)]
+pub mod wasm;
+
+#[cfg(feature = "tunnel")]
+mod tunnel;
+#[cfg(feature = "tunnel")]
+pub use tunnel::{TunnelError, TunnelReceiver, TunnelSender};
+
+#[cfg(feature = "batch")]
+#[allow(unsafe_code)]
+mod batch;
+#[cfg(feature = "batch")]
+pub use batch::BatchSocket;
+
+#[cfg(feature = "embassy")]
+mod embassy;
+#[cfg(feature = "embassy")]
+pub use embassy::{EmbassyError, EmbassySocket};
+
+#[cfg(feature = "smoltcp")]
+mod smoltcp_poll;
+#[cfg(feature = "smoltcp")]
+pub use smoltcp_poll::{receive_packets, send_packet};
+
+#[cfg(feature = "chrono")]
+mod chrono_impls;
+#[cfg(feature = "jiff")]
+mod jiff_impls;
+#[cfg(feature = "time")]
+mod time_impls;
+#[cfg(feature = "uom")]
+mod uom_impls;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "serde")]
+pub use serde_impls::Readable;
+#[cfg(feature = "schemars")]
+mod schemars_impls;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{Record, Recorder, RecorderError};
+
+#[cfg(any(test, feature = "arbitrary", feature = "proptest"))]
+mod generate;
+#[cfg(any(test, feature = "proptest"))]
+mod proptest_impls;
+
 // NOTE: We only implement CoE v2.0 for now.
 // Parsing a CoE packet of other versions will return an apropriate error.
 
@@ -167,23 +304,38 @@ pub use packet_no_alloc::Packet;
 /// Convert a slice of [Payload]s into (possibly multiple) [Packet]s.
 ///
 /// This is infallible and always creates enough [Packet]s to pack all [Payload]s into.
+/// The [Payload]s are packed as given, so invalid [AnalogueCOEValue::Date]s are sent like
+/// TA-Hardware would. Use [try_packets_from_payloads] to reject them instead.
 ///
 /// This function is available only on the `alloc` feature flag.
-///
-/// # Panics
-///
-/// On programmer errors, when the maximum size of a [Packet] was not respected.
 #[cfg(feature = "alloc")]
 pub fn packets_from_payloads(payloads: &[Payload]) -> Vec<Packet> {
     payloads
         .chunks(31)
-        .map(|c| Packet::try_from_payloads(c).expect("Length should be satisfied by chunking"))
+        .map(Packet::from_payloads_unchecked)
         .collect::<Vec<Packet>>()
 }
 
+/// Convert a slice of [Payload]s into (possibly multiple) [Packet]s, validating them.
+///
+/// Like [packets_from_payloads], but fails if one of the [Payload]s contains an invalid
+/// [AnalogueCOEValue::Date].
+///
+/// This function is available only on the `alloc` feature flag.
+#[cfg(feature = "alloc")]
+pub fn try_packets_from_payloads(payloads: &[Payload]) -> Option<Vec<Packet>> {
+    payloads
+        .chunks(31)
+        .map(Packet::try_from_payloads)
+        .collect::<Option<Vec<Packet>>>()
+}
+
 /// The Version of COE protocol used.
 #[derive(Hash, Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_impls::COEVersionRepr"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct COEVersion {
     /// The major CoE Version. Only 2 is supported.
     major: u8,
@@ -191,21 +343,37 @@ pub struct COEVersion {
     minor: u8,
 }
 impl COEVersion {
-    pub fn major(&self) -> u8 {
+    /// CoE version 2.0, the only supported version.
+    pub const V2_0: COEVersion = COEVersion { major: 2, minor: 0 };
+
+    /// Create a [COEVersion], failing for versions other then 2.0.
+    ///
+    /// ```rust
+    /// # use coe::COEVersion;
+    /// const VERSION: COEVersion = match COEVersion::new(2, 0) {
+    ///     Ok(version) => version,
+    ///     Err(_) => panic!("unsupported CoE version"),
+    /// };
+    /// assert_eq!(VERSION, COEVersion::V2_0);
+    /// ```
+    pub const fn new(major: u8, minor: u8) -> Result<COEVersion, ParseCOEError> {
+        // we only implement version 2.0 right now.
+        if major != 2 || minor != 0 {
+            return Err(ParseCOEError::VersionNotImplemented(major, minor));
+        };
+        Ok(Self::V2_0)
+    }
+    pub const fn major(&self) -> u8 {
         self.major
     }
-    pub fn minor(&self) -> u8 {
+    pub const fn minor(&self) -> u8 {
         self.minor
     }
 }
 impl TryFrom<(u8, u8)> for COEVersion {
     type Error = ParseCOEError;
     fn try_from(value: (u8, u8)) -> Result<Self, Self::Error> {
-        // we only implement version 2.0 right now.
-        if value.0 != 2 || value.1 != 0 {
-            return Err(Self::Error::VersionNotImplemented(value.0, value.1));
-        };
-        Ok(COEVersion { major: 2, minor: 0 })
+        COEVersion::new(value.0, value.1)
     }
 }
 impl core::fmt::Display for COEVersion {
@@ -222,7 +390,10 @@ impl core::fmt::Display for COEVersion {
 /// NOTE: the pdo_index is offset by one to the representation in the GUI.
 /// We store the on-wire format here, without the +1 offset present in the GUIs.
 #[derive(Hash, Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serde_impls::PayloadRepr"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Payload {
     /// The receiving CAN bus will create a virtual CAN node with this node number to send CAN
     /// messages onto the bus from.
@@ -239,15 +410,36 @@ impl TryFrom<&[u8]> for Payload {
     type Error = ParseCOEError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(value, false)
+    }
+}
+impl Payload {
+    /// Parse a `&[u8]` into a Payload, accepting [AnalogueCOEValue::Date]s that do not exist in
+    /// the calendar (e.g. the 30th of february), like TA-Hardware does.
+    ///
+    /// ```rust
+    /// # use coe::{AnalogueCOEValue, COEValue, Payload};
+    /// let raw_bytes = [1, 1, 1, 62, 30, 2, 0xe8, 0x07];
+    /// assert!(Payload::try_from(&raw_bytes[..]).is_err());
+    /// let payload = Payload::try_from_permissive(&raw_bytes).unwrap();
+    /// assert_eq!(payload.value(), COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2024)));
+    /// ```
+    pub fn try_from_permissive(value: &[u8]) -> Result<Self, ParseCOEError> {
+        Self::parse(value, true)
+    }
+
+    /// Parse a `&[u8]` into a Payload, skipping date validation if `permissive`.
+    pub(crate) fn parse(value: &[u8], permissive: bool) -> Result<Self, ParseCOEError> {
+        // check the length first, so that indexing below can never panic
+        if value.len() != 8 {
+            return Err(ParseCOEError::PayloadFrameLengthIncorrect(value.len()));
+        };
         // bound check the node and pdo_index values:
         if value[0] == 0 || value[0] >= 63 {
-            return Err(Self::Error::NodeDisallowed(value[0]));
+            return Err(ParseCOEError::NodeDisallowed(value[0]));
         };
         if value[1] >= 64 {
-            return Err(Self::Error::PDOIndexDisallowed(value[1]));
-        };
-        if value.len() != 8 {
-            return Err(Self::Error::PayloadFrameLengthIncorrect(value.len()));
+            return Err(ParseCOEError::PDOIndexDisallowed(value[1]));
         };
         // read the format and unit value.
         // if they do not fit, return an Error
@@ -262,29 +454,32 @@ impl TryFrom<&[u8]> for Payload {
                 })
             }
             1 => {
-                let coe_value: AnalogueCOEValue = (&value[3], &value[4..8]).try_into()?;
+                let coe_value = AnalogueCOEValue::parse(&value[3], &value[4..8], permissive)?;
                 Ok(Payload {
                     node: value[0],
                     pdo_index: value[1],
                     value: COEValue::Analogue(coe_value),
                 })
             }
-            _ => Err(Self::Error::FormatUnknown(value[2])),
+            _ => Err(ParseCOEError::FormatUnknown(value[2])),
         }
     }
 }
 impl core::default::Default for Payload {
     fn default() -> Payload {
-        Payload {
-            node: 1,
-            pdo_index: 0,
-            value: COEValue::Analogue(AnalogueCOEValue::Dimensionless(0)),
-        }
+        Payload::DEFAULT
     }
 }
 impl Payload {
+    /// The [Default] payload, usable in const context.
+    pub(crate) const DEFAULT: Payload = Payload {
+        node: 1,
+        pdo_index: 0,
+        value: COEValue::Analogue(AnalogueCOEValue::Dimensionless(0)),
+    };
+
     /// Create a new payload from the given destination and value.
-    pub fn new(node: u8, pdo_index: u8, value: COEValue) -> Payload {
+    pub const fn new(node: u8, pdo_index: u8, value: COEValue) -> Payload {
         Payload {
             node,
             pdo_index,
@@ -294,47 +489,71 @@ impl Payload {
 
     /// Serialize this [Payload] into the given buffer
     /// the buffer MUST have length == 8
-    /// the buffer MUST contain 0s in positions 5..8
     fn serialize_into(&self, buf: &mut [u8]) {
         // The only reason for this to not be satisfied is our internal code
         // passing the wrong buffer length, which should never happen.
-        assert_eq!(buf.len(), 8);
-        // write the node and pdo
-        buf[0] = self.node;
-        buf[1] = self.pdo_index;
-        // now write the value
-        self.value.serialize_into(&mut buf[2..8]);
+        buf.copy_from_slice(&self.wire_bytes());
+    }
+
+    /// The 8 bytes of the on-wire representation of this [Payload].
+    pub(crate) const fn wire_bytes(&self) -> [u8; 8] {
+        let (format, value) = match self.value {
+            COEValue::Analogue(x) => (1, x.value_bytes()),
+            COEValue::Digital(x) => (0, [x.value() as u8, 0, 0, 0]),
+        };
+        [
+            self.node,
+            self.pdo_index,
+            format,
+            self.value.unit_id(),
+            value[0],
+            value[1],
+            value[2],
+            value[3],
+        ]
     }
 
     /// Get the CAN-ID of the node this payload is sent to
-    pub fn node(&self) -> u8 {
+    pub const fn node(&self) -> u8 {
         self.node
     }
 
     /// Get the pdo_index this payload is sent to
-    pub fn pdo_index(&self) -> u8 {
+    pub const fn pdo_index(&self) -> u8 {
         self.pdo_index
     }
 
     /// Get the Format (Analogue | Digital) contained in this payload
-    pub fn format(&self) -> Format {
+    pub const fn format(&self) -> Format {
         self.value.format()
     }
 
     /// Get the Unit ID sent in this payload
-    pub fn unit_id(&self) -> u8 {
+    pub const fn unit_id(&self) -> u8 {
         self.value.unit_id()
     }
 
     /// Get the Value sent in this payload
-    pub fn value(&self) -> COEValue {
+    pub const fn value(&self) -> COEValue {
         self.value
     }
+
+    /// Check whether this payload may be added to a [Packet].
+    ///
+    /// This fails only for invalid [AnalogueCOEValue::Date]s.
+    pub(crate) const fn check(&self) -> Result<(), DateError> {
+        match &self.value {
+            COEValue::Analogue(x) => date::check_date(x),
+            COEValue::Digital(_) => Ok(()),
+        }
+    }
 }
 
 /// Any Value that is representable in COE.
 #[derive(Hash, Debug, PartialEq, Copy, Clone, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum COEValue {
     /// An `analogue` Value.
     ///
@@ -358,31 +577,14 @@ impl From<DigitalCOEValue> for COEValue {
     }
 }
 impl COEValue {
-    /// Serialize this [COEValue] into the given buffer
-    /// the buffer MUST have length == 6
-    /// the buffer MUST contain 0s in positions 3..6
-    fn serialize_into(&self, buf: &mut [u8]) {
-        assert_eq!(buf.len(), 6);
-        match self {
-            COEValue::Analogue(x) => {
-                buf[0] = 1;
-                x.serialize_into(&mut buf[1..6]);
-            }
-            COEValue::Digital(x) => {
-                buf[0] = 0;
-                x.serialize_into(&mut buf[1..6]);
-            }
-        };
-    }
-
     /// Return the format (Analogue | Digital) of this value
-    pub fn format(&self) -> Format {
+    pub const fn format(&self) -> Format {
         match self {
             Self::Analogue(_) => Format::Analogue,
             Self::Digital(_) => Format::Digital,
         }
     }
-    pub fn unit_id(&self) -> u8 {
+    pub const fn unit_id(&self) -> u8 {
         match self {
             Self::Analogue(x) => x.unit_id(),
             Self::Digital(x) => x.unit_id(),
@@ -390,836 +592,327 @@ impl COEValue {
     }
 }
 
-/// Convert a day and month into the internal format used in CoE.
-///
-/// NOTE: This does not check whether the month actually has that day.
-/// (e.g. this will allow the 30th of february)
+/// Generate [AnalogueCOEValue] and everything derived from its list of units.
 ///
-/// Returns `None` when `day` or `month` are out of bounds.
+/// Every unit is listed exactly once, as `Variant(fields) = unit_id => (decimals, symbol)`.
+/// Convertible units append `; Dimension, factor, offset`, such that `raw * factor + offset` is the
+/// value in the base unit of that [Dimension]. From this list, the enum itself, parsing,
+/// serialization, [AnalogueCOEValue::unit_id], the decimals and symbols, the unit conversions and
+/// [AnalogueCOEValue::UNIT_IDS] are generated, so adding a unit is a one-line change.
 ///
-/// Example:
-/// ```rust
-/// use coe::{AnalogueCOEValue, to_day_of_month};
-/// let val = to_day_of_month(17, 6);
-/// assert_eq!(val, Some(AnalogueCOEValue::DayOfMonth(171)));
-/// let val = to_day_of_month(58, 6);
-/// assert_eq!(val, None);
-/// let val = to_day_of_month(9, 14);
-/// assert_eq!(val, None);
-/// ```
-pub fn to_day_of_month(day: u8, month: u8) -> Option<AnalogueCOEValue> {
-    if day > 31 {
-        return None;
-    }
-    if month > 12 {
-        return None;
-    }
-    Some(AnalogueCOEValue::DayOfMonth(
-        i32::from(day - 1) + i32::from(month - 1) * 31,
-    ))
-}
+/// Units hold an `i32`, except for [AnalogueCOEValue::Date], which is the only unit with fields
+/// `(u8, u8, u16)`.
+macro_rules! analogue_units {
+    (
+        $(#[$enum_attr:meta])*
+        pub enum $enum:ident {
+            $(
+                $(#[$attr:meta])*
+                $name:ident($($field:ident),+) = $id:literal => (
+                    $decimals:literal, $symbol:literal
+                    $(; $dimension:ident, $factor:expr, $offset:expr)?
+                ),
+            )*
+        }
+    ) => {
+        $(#[$enum_attr])*
+        pub enum $enum {
+            $(
+                $(#[$attr])*
+                $name($($field),+) = $id,
+            )*
+        }
 
-/// The Errors that can occur when parsing an integer as day of month
-#[derive(Hash, Debug, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub enum FromDayOfMonthError {
-    /// The supplied AnalogueCOEValue was not DayOfMonth
-    NotDayOfMonth,
-    /// The value is not in bounds for regular FromDayOfMonth.
-    /// Note that TA-Hardware always accepts the Value, but assignes useless values here instead.
-    ValueOutOfBounds(i32),
-}
-impl core::fmt::Display for FromDayOfMonthError {
-    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        match self {
-            Self::NotDayOfMonth => write!(f, "Value was not DayOfMonth"),
-            Self::ValueOutOfBounds(x) => {
-                write!(f, "The Value {x} cannot be parsed as a day, month pair.")
+        /// Given the Format and raw value in bytes, try to create the [AnalogueCOEValue]
+        impl TryFrom<(&u8, &[u8])> for $enum {
+            type Error = ParseCOEError;
+            fn try_from(value: (&u8, &[u8])) -> Result<Self, Self::Error> {
+                Self::parse(value.0, value.1, false)
             }
         }
-    }
-}
-#[cfg(feature = "std")]
-impl std::error::Error for FromDayOfMonthError {}
 
-/// Convert the internal format for [AnalogueCOEValue::DayOfMonth] into two u8s containing day and month
-///
-/// Example:
-/// ```rust
-/// # use coe::{AnalogueCOEValue, from_day_of_month, FromDayOfMonthError};
-/// let val = from_day_of_month(AnalogueCOEValue::DayOfMonth(173));
-/// assert_eq!(val, Ok((19, 6)));
-///
-/// let val = from_day_of_month(AnalogueCOEValue::DayOfMonth(-1234));
-/// assert_eq!(val, Err(FromDayOfMonthError::ValueOutOfBounds(-1234)));
-///
-/// let val = from_day_of_month(AnalogueCOEValue::DegreeKelvin_Tens(123));
-/// assert_eq!(val, Err(FromDayOfMonthError::NotDayOfMonth));
-/// ```
-/// ```rust
-/// use coe::{AnalogueCOEValue, from_day_of_month, to_day_of_month};
-/// assert_eq!(from_day_of_month(to_day_of_month(9, 12).unwrap()), Ok((9, 12)));
-/// ```
-///
-/// # Panics
-///
-/// Panics on programmer errors, when out-of-bounds checks for the contained value are wrong.
-// Why is this not a method on AnalogueCOEValue?
-// This conversion only makes sense when we have a DayOfMonth value.
-// Keeping this function separate prevents IDEs from showing this method for all AnalogueCOEValues.
-pub fn from_day_of_month(value: AnalogueCOEValue) -> Result<(u8, u8), FromDayOfMonthError> {
-    match value {
-        AnalogueCOEValue::DayOfMonth(x) => {
-            if !(0..=30 + 31 * 11).contains(&x) {
-                Err(FromDayOfMonthError::ValueOutOfBounds(x))
-            } else {
-                Ok((
-                    (1 + x % 31).try_into().expect("Modulo 31 yields u8"),
-                    (1 + x / 31).try_into().expect("x should be smaller then 31 * 12 such that x / 31 is way smaller then i32::MAX"),
-                ))
+        impl $enum {
+            /// Parse the unit ID and raw value bytes, skipping date validation if `permissive`.
+            pub(crate) fn parse(
+                unit_id: &u8,
+                bytes: &[u8],
+                permissive: bool,
+            ) -> Result<Self, ParseCOEError> {
+                let raw_bytes: [u8; 4] = bytes
+                    .try_into()
+                    .map_err(|_| ParseCOEError::ValueSize(bytes.len()))?;
+                match unit_id {
+                    $($id => analogue_units!(@decode $name, raw_bytes, permissive; $($field),+),)*
+                    m => Err(ParseCOEError::FormatAndUnitIncompatible(Format::Analogue, *m)),
+                }
             }
-        }
-        _ => Err(FromDayOfMonthError::NotDayOfMonth),
-    }
-}
 
-/// Convert a month and year into a [AnalogueCOEValue::DayOfMonth].
-///
-/// Returns `None` when `month` is out of bounds.
-///
-/// Example:
-/// ```rust
-/// # use coe::{AnalogueCOEValue, to_month_of_year};
-/// let val = to_month_of_year(8, 1852);
-/// assert_eq!(val, Some(AnalogueCOEValue::MonthOfYear(22231)));
-///
-/// let val = to_month_of_year(58, 6);
-/// assert_eq!(val, None);
-/// ```
-pub fn to_month_of_year(month: u8, year: u16) -> Option<AnalogueCOEValue> {
-    if month > 12 {
-        return None;
-    }
-    Some(AnalogueCOEValue::MonthOfYear(
-        i32::from(month - 1) + i32::from(year) * 12,
-    ))
-}
+            /// The IDs of all analogue units, in ascending order.
+            pub const UNIT_IDS: &'static [u8] = &[$($id),*];
 
-/// The Errors that can occur when parsing an integer as day of month
-#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub enum FromMonthOfYearError {
-    /// The supplied AnalogueCOEValue was not MonthOfYear.
-    NotMonthOfYear,
-    /// The value is not in bounds for regular FromDayOfMonth.
-    /// Note that TA-Hardware always accepts the Value, but assignes useless values here instead.
-    ValueOutOfBounds(i32),
-}
-impl core::fmt::Display for FromMonthOfYearError {
-    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        match self {
-            Self::NotMonthOfYear => write!(f, "Value was not MonthOfYear"),
-            Self::ValueOutOfBounds(x) => {
-                write!(f, "The Value {x} cannot be parsed as a month, year pair.")
+            /// The 4 value bytes of the on-wire representation.
+            const fn value_bytes(&self) -> [u8; 4] {
+                match *self {
+                    $(
+                        analogue_units!(@bind $name, x, y, z; $($field),+) =>
+                            analogue_units!(@encode x, y, z; $($field),+),
+                    )*
+                }
             }
-        }
-    }
-}
-#[cfg(feature = "std")]
-impl std::error::Error for FromMonthOfYearError {}
 
-/// Convert the internal format for [AnalogueCOEValue::MonthOfYear] into a `u8` and `u16` containing month and year
-///
-/// Example:
-/// ```rust
-/// # use coe::{AnalogueCOEValue, from_month_of_year, FromMonthOfYearError};
-/// let val = from_month_of_year(AnalogueCOEValue::MonthOfYear(22231));
-/// assert_eq!(val, Ok((8, 1852)));
-///
-/// let val = from_month_of_year(AnalogueCOEValue::MonthOfYear(13 * i32::from(u16::MAX)));
-/// assert_eq!(val, Err(FromMonthOfYearError::ValueOutOfBounds(13 * i32::from(u16::MAX))));
-///
-/// let val = from_month_of_year(AnalogueCOEValue::DegreeKelvin_Tens(123));
-/// assert_eq!(val, Err(FromMonthOfYearError::NotMonthOfYear));
-/// ```
-/// ```rust
-/// use coe::{AnalogueCOEValue, from_month_of_year, to_month_of_year};
-/// assert_eq!(from_month_of_year(to_month_of_year(5, 325).unwrap()), Ok((5, 325)));
-/// ```
-///
-/// # Panics
-///
-/// Panics on programmer errors, when out-of-bounds checks for the contained value are wrong.
-// Why is this not a method on AnalogueCOEValue?
-// This conversion only makes sense when we have a MonthOfYear value.
-// Keeping this function separate prevents IDEs from showing this method for all AnalogueCOEValues.
-pub fn from_month_of_year(value: AnalogueCOEValue) -> Result<(u8, u16), FromMonthOfYearError> {
-    match value {
-        AnalogueCOEValue::MonthOfYear(x) => {
-            if (0..=11 + 12 * i32::from(u16::MAX)).contains(&x) {
-                Ok((
-                    (1 + x % 12).try_into().expect("Modulo 12 has image inside u8"),
-                    (x / 12).try_into().expect("x should be smaller then 11 + 12 * u16::MAX and therefore devisible by 12 into a u16"),
-                ))
-            } else {
-                Err(FromMonthOfYearError::ValueOutOfBounds(x))
+            pub const fn unit_id(&self) -> u8 {
+                match self {
+                    $(Self::$name(..) => $id,)*
+                }
             }
-        }
-        _ => Err(FromMonthOfYearError::NotMonthOfYear),
-    }
-}
 
-/// All the different analogue values representable in CoE.
-/// Ordering (and therefore numbering) is the one used internally in the CoE spec.
-#[repr(u8)]
-// We allow non_camel_case_types here, so that we can better separate the comma position from the
-// actual content here (I think this is the cleaner naming scheme in this particular case)
-#[allow(non_camel_case_types)]
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub enum AnalogueCOEValue {
-    Dimensionless(i32) = 0,
-    DegreeCentigrade_Tens(i32) = 1,
-    WattPerSquareMeter(i32) = 2,
-    LiterPerHour(i32) = 3,
-    Seconds(i32) = 4,
-    Minutes(i32) = 5,
-    LiterPerPulse_Tens(i32) = 6,
-    DegreeKelvin_Tens(i32) = 7,
-    Percent_Tens(i32) = 8,
-    Colon(i32) = 9,
-    KiloWatt_Hundreds(i32) = 10,
-    KilowattHour_Tens(i32) = 11,
-    MegawattHour(i32) = 12,
-    Volt_Hundreds(i32) = 13,
-    MilliAmpere_Tens(i32) = 14,
-    Hours(i32) = 15,
-    Days(i32) = 16,
-    Pulses(i32) = 17,
-    KiloOhm_Hundreds(i32) = 18,
-    Liters(i32) = 19,
-    KiloMetersPerHour(i32) = 20,
-    Hertz_Hundreds(i32) = 21,
-    LiterPerMinute(i32) = 22,
-    Bar_Hundreds(i32) = 23,
-    CoefficientOfPerformance_Hundreds(i32) = 24,
-    KiloMeter(i32) = 25,
-    Meter_Tens(i32) = 26,
-    MilliMeter(i32) = 27,
-    CubicMeter(i32) = 28,
-    HertzPerKiloMeterPerHour_HundredThousands(i32) = 29,
-    // Note: the documentation is incorrect here and lists this as Hz/km/s
-    HertzPerMeterPerSecond_HundredThousands(i32) = 30,
-    KilowattHourPerPulse_HundredThousands(i32) = 31,
-    CubicMeterPerPulse_HundredThousands(i32) = 32,
-    MilliMeterPerPulse_HundredThousands(i32) = 33,
-    LiterPerPulse_HundredThousands(i32) = 34,
-    LiterPerDay(i32) = 35,
-    MetersPerSecond(i32) = 36,
-    CubicMeterPerMinute(i32) = 37,
-    CubicMeterPerHour(i32) = 38,
-    CubicMeterPerDay(i32) = 39,
-    MilliMeterPerMinute_Tens(i32) = 40,
-    MilliMeterPerHour_Tens(i32) = 41,
-    MilliMeterPerDay_Tens(i32) = 42,
-    DegreeCentigradePlusRAS_Tens(i32) = 46,
-    HeatingCircuitOpMode(i32) = 48,
-    HeatingCircuitOpLevel(i32) = 49,
-    CurrencyEuro_Hundreds(i32) = 50,
-    CurrencyDollar_Hundreds(i32) = 51,
-    AbsoluteHumidity_Tens(i32) = 52,
-    PricePerUnit_HundredThousands(i32) = 53,
-    Degree_Tens(i32) = 54,
-    Blinds(i32) = 55,
-    Degree_Millions(i32) = 56,
-    Second_Tens(i32) = 57,
-    Dimensionless_Tens(i32) = 58,
-    BlindsPosition(i32) = 59,
-    /// Time, in minutes, represented as HH:MM
-    Time(i32) = 60,
-    /// Day of month.
-    /// `DayOfMonth(day - 1 + 31 * (month - 1))`
-    /// corresponds to the day in month
-    ///
-    /// Consider using the helper functions [from_day_of_month] and [to_day_of_month] for parsing.
-    DayOfMonth(i32) = 61,
-    /// Date, as:
-    /// days
-    /// months
-    /// years
-    Date(u8, u8, u16) = 62,
-    Ampere_Tens(i32) = 63,
-    /// Month + Year.
-    /// `MonthOfYear(year * 12 + month)` corresponds to month in year.
-    ///
-    /// Consider using the helper functions [from_month_of_year] and [to_month_of_year] for
-    /// parsing.
-    MonthOfYear(i32) = 64,
-    Millibar_Tens(i32) = 65,
-    Pascal(i32) = 66,
-    CO2Content(i32) = 67,
-    RawHex(i32) = 68,
-    Watt(i32) = 69,
-    Tonne_Hundreds(i32) = 70,
-    KiloGram_Tens(i32) = 71,
-    Gram_Tens(i32) = 72,
-    CentiMeter_Tens(i32) = 73,
-    ColourTemperature(i32) = 74,
-    Lux_Tens(i32) = 75,
-}
+            /// Get the number of decimal places and the symbol of the unit with ID `unit_id`.
+            ///
+            /// Units without a physical unit (e.g. [AnalogueCOEValue::Dimensionless] or the coded
+            /// units) have an empty symbol.
+            pub(crate) const fn meta(unit_id: u8) -> Option<(u8, &'static str)> {
+                match unit_id {
+                    $($id => Some(($decimals, $symbol)),)*
+                    _ => None,
+                }
+            }
 
-/// Given the Format and raw value in bytes, try to create the [AnalogueCOEValue]
-impl TryFrom<(&u8, &[u8])> for AnalogueCOEValue {
-    type Error = ParseCOEError;
-    fn try_from(value: (&u8, &[u8])) -> Result<Self, Self::Error> {
-        let raw_bytes: [u8; 4] = value
-            .1
-            .try_into()
-            .map_err(|_| Self::Error::ValueSize(value.1.len()))?;
-        let inner_value = i32::from_le_bytes(raw_bytes);
-        match value.0 {
-            0 => Ok(Self::Dimensionless(inner_value)),
-            1 => Ok(Self::DegreeCentigrade_Tens(inner_value)),
-            2 => Ok(Self::WattPerSquareMeter(inner_value)),
-            3 => Ok(Self::LiterPerHour(inner_value)),
-            4 => Ok(Self::Seconds(inner_value)),
-            5 => Ok(Self::Minutes(inner_value)),
-            6 => Ok(Self::LiterPerPulse_Tens(inner_value)),
-            7 => Ok(Self::DegreeKelvin_Tens(inner_value)),
-            8 => Ok(Self::Percent_Tens(inner_value)),
-            9 => Ok(Self::Colon(inner_value)),
-            10 => Ok(Self::KiloWatt_Hundreds(inner_value)),
-            11 => Ok(Self::KilowattHour_Tens(inner_value)),
-            12 => Ok(Self::MegawattHour(inner_value)),
-            13 => Ok(Self::Volt_Hundreds(inner_value)),
-            14 => Ok(Self::MilliAmpere_Tens(inner_value)),
-            15 => Ok(Self::Hours(inner_value)),
-            16 => Ok(Self::Days(inner_value)),
-            17 => Ok(Self::Pulses(inner_value)),
-            18 => Ok(Self::KiloOhm_Hundreds(inner_value)),
-            19 => Ok(Self::Liters(inner_value)),
-            20 => Ok(Self::KiloMetersPerHour(inner_value)),
-            21 => Ok(Self::Hertz_Hundreds(inner_value)),
-            22 => Ok(Self::LiterPerMinute(inner_value)),
-            23 => Ok(Self::Bar_Hundreds(inner_value)),
-            24 => Ok(Self::CoefficientOfPerformance_Hundreds(inner_value)),
-            25 => Ok(Self::KiloMeter(inner_value)),
-            26 => Ok(Self::Meter_Tens(inner_value)),
-            27 => Ok(Self::MilliMeter(inner_value)),
-            28 => Ok(Self::CubicMeter(inner_value)),
-            29 => Ok(Self::HertzPerKiloMeterPerHour_HundredThousands(inner_value)),
-            30 => Ok(Self::HertzPerMeterPerSecond_HundredThousands(inner_value)),
-            31 => Ok(Self::KilowattHourPerPulse_HundredThousands(inner_value)),
-            32 => Ok(Self::CubicMeterPerPulse_HundredThousands(inner_value)),
-            33 => Ok(Self::MilliMeterPerPulse_HundredThousands(inner_value)),
-            34 => Ok(Self::LiterPerPulse_HundredThousands(inner_value)),
-            35 => Ok(Self::LiterPerDay(inner_value)),
-            36 => Ok(Self::MetersPerSecond(inner_value)),
-            37 => Ok(Self::CubicMeterPerMinute(inner_value)),
-            38 => Ok(Self::CubicMeterPerHour(inner_value)),
-            39 => Ok(Self::CubicMeterPerDay(inner_value)),
-            40 => Ok(Self::MilliMeterPerMinute_Tens(inner_value)),
-            41 => Ok(Self::MilliMeterPerHour_Tens(inner_value)),
-            42 => Ok(Self::MilliMeterPerDay_Tens(inner_value)),
-            46 => Ok(Self::DegreeCentigradePlusRAS_Tens(inner_value)),
-            48 => Ok(Self::HeatingCircuitOpMode(inner_value)),
-            49 => Ok(Self::HeatingCircuitOpLevel(inner_value)),
-            50 => Ok(Self::CurrencyEuro_Hundreds(inner_value)),
-            51 => Ok(Self::CurrencyDollar_Hundreds(inner_value)),
-            52 => Ok(Self::AbsoluteHumidity_Tens(inner_value)),
-            53 => Ok(Self::PricePerUnit_HundredThousands(inner_value)),
-            54 => Ok(Self::Degree_Tens(inner_value)),
-            55 => Ok(Self::Blinds(inner_value)),
-            56 => Ok(Self::Degree_Millions(inner_value)),
-            57 => Ok(Self::Second_Tens(inner_value)),
-            58 => Ok(Self::Dimensionless_Tens(inner_value)),
-            59 => Ok(Self::BlindsPosition(inner_value)),
-            60 => Ok(Self::Time(inner_value)),
-            61 => Ok(Self::DayOfMonth(inner_value)),
-            62 => {
-                let bytes = inner_value.to_le_bytes();
-                let days = bytes[0];
-                let months = bytes[1];
-                let years: u16 = u16::from(bytes[2]) + (u16::from(bytes[3]) << 8);
-                Ok(Self::Date(days, months, years))
+            /// Get the dimension of the unit with ID `unit_id`, together with `factor` and `offset`
+            /// such that `raw * factor + offset` is the value in the base unit of that dimension.
+            pub(crate) const fn scale(unit_id: u8) -> Option<(Dimension, i64, i64)> {
+                match unit_id {
+                    $($id => analogue_units!(@scale $($dimension, $factor, $offset)?),)*
+                    _ => None,
+                }
             }
-            63 => Ok(Self::Ampere_Tens(inner_value)),
-            64 => Ok(Self::MonthOfYear(inner_value)),
-            65 => Ok(Self::Millibar_Tens(inner_value)),
-            66 => Ok(Self::Pascal(inner_value)),
-            67 => Ok(Self::CO2Content(inner_value)),
-            68 => Ok(Self::RawHex(inner_value)),
-            69 => Ok(Self::Watt(inner_value)),
-            70 => Ok(Self::Tonne_Hundreds(inner_value)),
-            71 => Ok(Self::KiloGram_Tens(inner_value)),
-            72 => Ok(Self::Gram_Tens(inner_value)),
-            73 => Ok(Self::CentiMeter_Tens(inner_value)),
-            74 => Ok(Self::ColourTemperature(inner_value)),
-            75 => Ok(Self::Lux_Tens(inner_value)),
-            m => Err(Self::Error::FormatAndUnitIncompatible(Format::Analogue, *m)),
         }
-    }
-}
-impl AnalogueCOEValue {
-    /// Serialize this [AnalogueCOEValue] into the given buffer.
-    /// The buffer MUST be of length == 5
-    fn serialize_into(&self, buf: &mut [u8]) {
-        assert_eq!(buf.len(), 5);
-        match self {
-            Self::Dimensionless(x) => {
-                buf[0] = 0;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::DegreeCentigrade_Tens(x) => {
-                buf[0] = 1;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::WattPerSquareMeter(x) => {
-                buf[0] = 2;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::LiterPerHour(x) => {
-                buf[0] = 3;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Seconds(x) => {
-                buf[0] = 4;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Minutes(x) => {
-                buf[0] = 5;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::LiterPerPulse_Tens(x) => {
-                buf[0] = 6;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::DegreeKelvin_Tens(x) => {
-                buf[0] = 7;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Percent_Tens(x) => {
-                buf[0] = 8;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Colon(x) => {
-                buf[0] = 9;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KiloWatt_Hundreds(x) => {
-                buf[0] = 10;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KilowattHour_Tens(x) => {
-                buf[0] = 11;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MegawattHour(x) => {
-                buf[0] = 12;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Volt_Hundreds(x) => {
-                buf[0] = 13;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliAmpere_Tens(x) => {
-                buf[0] = 14;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Hours(x) => {
-                buf[0] = 15;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Days(x) => {
-                buf[0] = 16;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Pulses(x) => {
-                buf[0] = 17;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KiloOhm_Hundreds(x) => {
-                buf[0] = 18;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Liters(x) => {
-                buf[0] = 19;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KiloMetersPerHour(x) => {
-                buf[0] = 20;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Hertz_Hundreds(x) => {
-                buf[0] = 21;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::LiterPerMinute(x) => {
-                buf[0] = 22;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Bar_Hundreds(x) => {
-                buf[0] = 23;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CoefficientOfPerformance_Hundreds(x) => {
-                buf[0] = 24;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KiloMeter(x) => {
-                buf[0] = 25;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Meter_Tens(x) => {
-                buf[0] = 26;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliMeter(x) => {
-                buf[0] = 27;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CubicMeter(x) => {
-                buf[0] = 28;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::HertzPerKiloMeterPerHour_HundredThousands(x) => {
-                buf[0] = 29;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::HertzPerMeterPerSecond_HundredThousands(x) => {
-                buf[0] = 30;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KilowattHourPerPulse_HundredThousands(x) => {
-                buf[0] = 31;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CubicMeterPerPulse_HundredThousands(x) => {
-                buf[0] = 32;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliMeterPerPulse_HundredThousands(x) => {
-                buf[0] = 33;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::LiterPerPulse_HundredThousands(x) => {
-                buf[0] = 34;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::LiterPerDay(x) => {
-                buf[0] = 35;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MetersPerSecond(x) => {
-                buf[0] = 36;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CubicMeterPerMinute(x) => {
-                buf[0] = 37;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CubicMeterPerHour(x) => {
-                buf[0] = 38;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CubicMeterPerDay(x) => {
-                buf[0] = 39;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliMeterPerMinute_Tens(x) => {
-                buf[0] = 40;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliMeterPerHour_Tens(x) => {
-                buf[0] = 41;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MilliMeterPerDay_Tens(x) => {
-                buf[0] = 42;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::DegreeCentigradePlusRAS_Tens(x) => {
-                buf[0] = 46;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::HeatingCircuitOpMode(x) => {
-                buf[0] = 48;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::HeatingCircuitOpLevel(x) => {
-                buf[0] = 49;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CurrencyEuro_Hundreds(x) => {
-                buf[0] = 50;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CurrencyDollar_Hundreds(x) => {
-                buf[0] = 51;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::AbsoluteHumidity_Tens(x) => {
-                buf[0] = 52;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::PricePerUnit_HundredThousands(x) => {
-                buf[0] = 53;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Degree_Tens(x) => {
-                buf[0] = 54;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Blinds(x) => {
-                buf[0] = 55;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Degree_Millions(x) => {
-                buf[0] = 56;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Second_Tens(x) => {
-                buf[0] = 57;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Dimensionless_Tens(x) => {
-                buf[0] = 58;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::BlindsPosition(x) => {
-                buf[0] = 59;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Time(x) => {
-                buf[0] = 60;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::DayOfMonth(x) => {
-                buf[0] = 61;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Date(days, months, years) => {
-                buf[0] = 62;
-                buf[1] = *days;
-                buf[2] = *months;
-                buf[3..5].copy_from_slice(&years.to_le_bytes());
-            }
-            Self::Ampere_Tens(x) => {
-                buf[0] = 63;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::MonthOfYear(x) => {
-                buf[0] = 64;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Millibar_Tens(x) => {
-                buf[0] = 65;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Pascal(x) => {
-                buf[0] = 66;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CO2Content(x) => {
-                buf[0] = 67;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::RawHex(x) => {
-                buf[0] = 68;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Watt(x) => {
-                buf[0] = 69;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Tonne_Hundreds(x) => {
-                buf[0] = 70;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::KiloGram_Tens(x) => {
-                buf[0] = 71;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Gram_Tens(x) => {
-                buf[0] = 72;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::CentiMeter_Tens(x) => {
-                buf[0] = 73;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::ColourTemperature(x) => {
-                buf[0] = 74;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
-            Self::Lux_Tens(x) => {
-                buf[0] = 75;
-                buf[1..5].copy_from_slice(&x.to_le_bytes());
-            }
+    };
+    (@scale) => {
+        None
+    };
+    (@scale $dimension:ident, $factor:expr, $offset:expr) => {
+        Some((Dimension::$dimension, $factor, $offset))
+    };
+    (@decode $name:ident, $bytes:ident, $permissive:ident; i32) => {
+        Ok(Self::$name(i32::from_le_bytes($bytes)))
+    };
+    (@decode $name:ident, $bytes:ident, $permissive:ident; u8, u8, u16) => {{
+        let date = Self::$name($bytes[0], $bytes[1], u16::from_le_bytes([$bytes[2], $bytes[3]]));
+        if !$permissive {
+            date::check_date(&date).map_err(ParseCOEError::DateInvalid)?;
         };
-    }
+        Ok(date)
+    }};
+    (@bind $name:ident, $x:ident, $y:ident, $z:ident; i32) => {
+        Self::$name($x)
+    };
+    (@bind $name:ident, $x:ident, $y:ident, $z:ident; u8, u8, u16) => {
+        Self::$name($x, $y, $z)
+    };
+    (@encode $x:ident, $y:ident, $z:ident; i32) => {
+        $x.to_le_bytes()
+    };
+    (@encode $x:ident, $y:ident, $z:ident; u8, u8, u16) => {{
+        let years = $z.to_le_bytes();
+        [$x, $y, years[0], years[1]]
+    }};
+}
 
-    pub fn unit_id(&self) -> u8 {
-        match self {
-            Self::Dimensionless(_) => 0,
-            Self::DegreeCentigrade_Tens(_) => 1,
-            Self::WattPerSquareMeter(_) => 2,
-            Self::LiterPerHour(_) => 3,
-            Self::Seconds(_) => 4,
-            Self::Minutes(_) => 5,
-            Self::LiterPerPulse_Tens(_) => 6,
-            Self::DegreeKelvin_Tens(_) => 7,
-            Self::Percent_Tens(_) => 8,
-            Self::Colon(_) => 9,
-            Self::KiloWatt_Hundreds(_) => 10,
-            Self::KilowattHour_Tens(_) => 11,
-            Self::MegawattHour(_) => 12,
-            Self::Volt_Hundreds(_) => 13,
-            Self::MilliAmpere_Tens(_) => 14,
-            Self::Hours(_) => 15,
-            Self::Days(_) => 16,
-            Self::Pulses(_) => 17,
-            Self::KiloOhm_Hundreds(_) => 18,
-            Self::Liters(_) => 19,
-            Self::KiloMetersPerHour(_) => 20,
-            Self::Hertz_Hundreds(_) => 21,
-            Self::LiterPerMinute(_) => 22,
-            Self::Bar_Hundreds(_) => 23,
-            Self::CoefficientOfPerformance_Hundreds(_) => 24,
-            Self::KiloMeter(_) => 25,
-            Self::Meter_Tens(_) => 26,
-            Self::MilliMeter(_) => 27,
-            Self::CubicMeter(_) => 28,
-            Self::HertzPerKiloMeterPerHour_HundredThousands(_) => 29,
-            Self::HertzPerMeterPerSecond_HundredThousands(_) => 30,
-            Self::KilowattHourPerPulse_HundredThousands(_) => 31,
-            Self::CubicMeterPerPulse_HundredThousands(_) => 32,
-            Self::MilliMeterPerPulse_HundredThousands(_) => 33,
-            Self::LiterPerPulse_HundredThousands(_) => 34,
-            Self::LiterPerDay(_) => 35,
-            Self::MetersPerSecond(_) => 36,
-            Self::CubicMeterPerMinute(_) => 37,
-            Self::CubicMeterPerHour(_) => 38,
-            Self::CubicMeterPerDay(_) => 39,
-            Self::MilliMeterPerMinute_Tens(_) => 40,
-            Self::MilliMeterPerHour_Tens(_) => 41,
-            Self::MilliMeterPerDay_Tens(_) => 42,
-            Self::DegreeCentigradePlusRAS_Tens(_) => 46,
-            Self::HeatingCircuitOpMode(_) => 48,
-            Self::HeatingCircuitOpLevel(_) => 49,
-            Self::CurrencyEuro_Hundreds(_) => 50,
-            Self::CurrencyDollar_Hundreds(_) => 51,
-            Self::AbsoluteHumidity_Tens(_) => 52,
-            Self::PricePerUnit_HundredThousands(_) => 53,
-            Self::Degree_Tens(_) => 54,
-            Self::Blinds(_) => 55,
-            Self::Degree_Millions(_) => 56,
-            Self::Second_Tens(_) => 57,
-            Self::Dimensionless_Tens(_) => 58,
-            Self::BlindsPosition(_) => 59,
-            Self::Time(_) => 60,
-            Self::DayOfMonth(_) => 61,
-            Self::Date(_, _, _) => 62,
-            Self::Ampere_Tens(_) => 63,
-            Self::MonthOfYear(_) => 64,
-            Self::Millibar_Tens(_) => 65,
-            Self::Pascal(_) => 66,
-            Self::CO2Content(_) => 67,
-            Self::RawHex(_) => 68,
-            Self::Watt(_) => 69,
-            Self::Tonne_Hundreds(_) => 70,
-            Self::KiloGram_Tens(_) => 71,
-            Self::Gram_Tens(_) => 72,
-            Self::CentiMeter_Tens(_) => 73,
-            Self::ColourTemperature(_) => 74,
-            Self::Lux_Tens(_) => 75,
-        }
+analogue_units! {
+    /// All the different analogue values representable in CoE.
+    /// Ordering (and therefore numbering) is the one used internally in the CoE spec.
+    #[repr(u8)]
+    // We allow non_camel_case_types here, so that we can better separate the comma position from the
+    // actual content here (I think this is the cleaner naming scheme in this particular case)
+    #[allow(non_camel_case_types)]
+    #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum AnalogueCOEValue {
+        Dimensionless(i32) = 0 => (0, ""; Dimensionless, 10, 0),
+        DegreeCentigrade_Tens(i32) = 1 => (1, "°C"; Temperature, 10, 27315),
+        WattPerSquareMeter(i32) = 2 => (0, "W/m²"),
+        LiterPerHour(i32) = 3 => (0, "l/h"; VolumeFlow, 24, 0),
+        Seconds(i32) = 4 => (0, "s"; Duration, 10, 0),
+        Minutes(i32) = 5 => (0, "min"; Duration, 600, 0),
+        LiterPerPulse_Tens(i32) = 6 => (1, "l/Imp"; VolumePerPulse, 10_000, 0),
+        DegreeKelvin_Tens(i32) = 7 => (1, "K"; Temperature, 10, 0),
+        Percent_Tens(i32) = 8 => (1, "%"),
+        /// Time, in minutes, represented as HH:MM (like [AnalogueCOEValue::Time])
+        Colon(i32) = 9 => (0, ""),
+        KiloWatt_Hundreds(i32) = 10 => (2, "kW"; Power, 10, 0),
+        KilowattHour_Tens(i32) = 11 => (1, "kWh"; Energy, 100, 0),
+        MegawattHour(i32) = 12 => (0, "MWh"; Energy, 1_000_000, 0),
+        Volt_Hundreds(i32) = 13 => (2, "V"),
+        MilliAmpere_Tens(i32) = 14 => (1, "mA"; ElectricCurrent, 1, 0),
+        Hours(i32) = 15 => (0, "h"; Duration, 36_000, 0),
+        Days(i32) = 16 => (0, "d"; Duration, 864_000, 0),
+        Pulses(i32) = 17 => (0, "Imp"),
+        KiloOhm_Hundreds(i32) = 18 => (2, "kΩ"),
+        Liters(i32) = 19 => (0, "l"; Volume, 1, 0),
+        KiloMetersPerHour(i32) = 20 => (0, "km/h"; Velocity, 1_000, 0),
+        Hertz_Hundreds(i32) = 21 => (2, "Hz"),
+        LiterPerMinute(i32) = 22 => (0, "l/min"; VolumeFlow, 24 * 60, 0),
+        Bar_Hundreds(i32) = 23 => (2, "bar"; Pressure, 1_000, 0),
+        CoefficientOfPerformance_Hundreds(i32) = 24 => (2, ""),
+        KiloMeter(i32) = 25 => (0, "km"; Length, 1_000_000, 0),
+        Meter_Tens(i32) = 26 => (1, "m"; Length, 100, 0),
+        MilliMeter(i32) = 27 => (0, "mm"; Length, 1, 0),
+        CubicMeter(i32) = 28 => (0, "m³"; Volume, 1_000, 0),
+        HertzPerKiloMeterPerHour_HundredThousands(i32) = 29 => (5, "Hz/km/h"),
+        // Note: the documentation is incorrect here and lists this as Hz/km/s
+        HertzPerMeterPerSecond_HundredThousands(i32) = 30 => (5, "Hz/m/s"),
+        KilowattHourPerPulse_HundredThousands(i32) = 31 => (5, "kWh/Imp"),
+        CubicMeterPerPulse_HundredThousands(i32) = 32 => (5, "m³/Imp"; VolumePerPulse, 1_000, 0),
+        MilliMeterPerPulse_HundredThousands(i32) = 33 => (5, "mm/Imp"),
+        LiterPerPulse_HundredThousands(i32) = 34 => (5, "l/Imp"; VolumePerPulse, 1, 0),
+        LiterPerDay(i32) = 35 => (0, "l/d"; VolumeFlow, 1, 0),
+        MetersPerSecond(i32) = 36 => (0, "m/s"; Velocity, 3_600, 0),
+        CubicMeterPerMinute(i32) = 37 => (0, "m³/min"; VolumeFlow, 1_000 * 24 * 60, 0),
+        CubicMeterPerHour(i32) = 38 => (0, "m³/h"; VolumeFlow, 1_000 * 24, 0),
+        CubicMeterPerDay(i32) = 39 => (0, "m³/d"; VolumeFlow, 1_000, 0),
+        MilliMeterPerMinute_Tens(i32) = 40 => (1, "mm/min"; PrecipitationRate, 24 * 60, 0),
+        MilliMeterPerHour_Tens(i32) = 41 => (1, "mm/h"; PrecipitationRate, 24, 0),
+        MilliMeterPerDay_Tens(i32) = 42 => (1, "mm/d"; PrecipitationRate, 1, 0),
+        DegreeCentigradePlusRAS_Tens(i32) = 46 => (1, "°C"),
+        HeatingCircuitOpMode(i32) = 48 => (0, ""),
+        HeatingCircuitOpLevel(i32) = 49 => (0, ""),
+        CurrencyEuro_Hundreds(i32) = 50 => (2, "€"),
+        CurrencyDollar_Hundreds(i32) = 51 => (2, "$"),
+        AbsoluteHumidity_Tens(i32) = 52 => (1, "g/m³"),
+        PricePerUnit_HundredThousands(i32) = 53 => (5, ""),
+        Degree_Tens(i32) = 54 => (1, "°"; Angle, 100_000, 0),
+        Blinds(i32) = 55 => (0, ""),
+        Degree_Millions(i32) = 56 => (6, "°"; Angle, 1, 0),
+        Second_Tens(i32) = 57 => (1, "s"; Duration, 1, 0),
+        Dimensionless_Tens(i32) = 58 => (1, ""; Dimensionless, 1, 0),
+        BlindsPosition(i32) = 59 => (0, ""),
+        /// Time, in minutes, represented as HH:MM
+        Time(i32) = 60 => (0, ""),
+        /// Day of month.
+        /// `DayOfMonth(day - 1 + 31 * (month - 1))`
+        /// corresponds to the day in month
+        ///
+        /// Consider using the helper functions [from_day_of_month] and [to_day_of_month] for parsing.
+        DayOfMonth(i32) = 61 => (0, ""),
+        /// Date, as:
+        /// days
+        /// months
+        /// years
+        Date(u8, u8, u16) = 62 => (0, ""),
+        Ampere_Tens(i32) = 63 => (1, "A"; ElectricCurrent, 1_000, 0),
+        /// Month + Year.
+        /// `MonthOfYear(year * 12 + month)` corresponds to month in year.
+        ///
+        /// Consider using the helper functions [from_month_of_year] and [to_month_of_year] for
+        /// parsing.
+        MonthOfYear(i32) = 64 => (0, ""),
+        Millibar_Tens(i32) = 65 => (1, "mbar"; Pressure, 10, 0),
+        Pascal(i32) = 66 => (0, "Pa"; Pressure, 1, 0),
+        CO2Content(i32) = 67 => (0, "ppm"),
+        RawHex(i32) = 68 => (0, ""),
+        Watt(i32) = 69 => (0, "W"; Power, 1, 0),
+        Tonne_Hundreds(i32) = 70 => (2, "t"; Mass, 100_000, 0),
+        KiloGram_Tens(i32) = 71 => (1, "kg"; Mass, 1_000, 0),
+        Gram_Tens(i32) = 72 => (1, "g"; Mass, 1, 0),
+        CentiMeter_Tens(i32) = 73 => (1, "cm"; Length, 1, 0),
+        ColourTemperature(i32) = 74 => (0, "K"),
+        Lux_Tens(i32) = 75 => (1, "lx"),
     }
 }
 
-/// Representation of all existing digital values representable in COE
-#[repr(u8)]
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-pub enum DigitalCOEValue {
-    OnOff(bool) = 43,
-    YesNo(bool) = 44,
-    RASMode(bool) = 45,
-    /// `true == Normal`,
-    /// `false == AUS`
-    Mixer(bool) = 47,
-}
-/// Given the Format and raw value in bytes, try to create the DigitalCOEValue
-impl TryFrom<(&u8, &[u8])> for DigitalCOEValue {
-    type Error = ParseCOEError;
-    fn try_from(value: (&u8, &[u8])) -> Result<Self, Self::Error> {
-        if value.1.len() != 4 {
-            return Err(Self::Error::ValueSize(value.1.len()));
-        };
+/// Generate [DigitalCOEValue] and everything derived from its list of units.
+///
+/// Every unit is listed exactly once, as `Variant(bool) = unit_id`. From this list, the enum itself,
+/// parsing, [DigitalCOEValue::unit_id], [DigitalCOEValue::value] and [DigitalCOEValue::UNIT_IDS]
+/// are generated.
+macro_rules! digital_units {
+    (
+        $(#[$enum_attr:meta])*
+        pub enum $enum:ident {
+            $(
+                $(#[$attr:meta])*
+                $name:ident(bool) = $id:literal,
+            )*
+        }
+    ) => {
+        $(#[$enum_attr])*
+        pub enum $enum {
+            $(
+                $(#[$attr])*
+                $name(bool) = $id,
+            )*
+        }
 
-        if value.1[3] != 0 || value.1[2] != 0 || value.1[1] != 0 {
-            return Err(Self::Error::ValueNotBool(
-                value
-                    .1
-                    .try_into()
-                    .expect("I already asserted that value.1 has four elements."),
-            ));
-        };
-        let inner_bool = match value.1[0] {
-            0 => false,
-            1 => true,
-            _ => {
-                return Err(Self::Error::ValueNotBool(
-                    value
-                        .1
-                        .try_into()
-                        .expect("I already asserted that value.1 has four elements."),
-                ));
+        /// Given the Format and raw value in bytes, try to create the DigitalCOEValue
+        impl TryFrom<(&u8, &[u8])> for $enum {
+            type Error = ParseCOEError;
+            fn try_from(value: (&u8, &[u8])) -> Result<Self, Self::Error> {
+                if value.1.len() != 4 {
+                    return Err(Self::Error::ValueSize(value.1.len()));
+                };
+
+                if value.1[3] != 0 || value.1[2] != 0 || value.1[1] != 0 {
+                    return Err(Self::Error::ValueNotBool(
+                        value
+                            .1
+                            .try_into()
+                            .expect("I already asserted that value.1 has four elements."),
+                    ));
+                };
+                let inner_bool = match value.1[0] {
+                    0 => false,
+                    1 => true,
+                    _ => {
+                        return Err(Self::Error::ValueNotBool(
+                            value
+                                .1
+                                .try_into()
+                                .expect("I already asserted that value.1 has four elements."),
+                        ));
+                    }
+                };
+                match value.0 {
+                    $($id => Ok(Self::$name(inner_bool)),)*
+                    m => Err(Self::Error::FormatAndUnitIncompatible(Format::Digital, *m)),
+                }
             }
-        };
-        match value.0 {
-            43 => Ok(Self::OnOff(inner_bool)),
-            44 => Ok(Self::YesNo(inner_bool)),
-            45 => Ok(Self::RASMode(inner_bool)),
-            47 => Ok(Self::Mixer(inner_bool)),
-            m => Err(Self::Error::FormatAndUnitIncompatible(Format::Digital, *m)),
         }
-    }
-}
-impl DigitalCOEValue {
-    /// Serialize this [DigitalCOEValue] into the given buffer.
-    /// The buffer MUST be of length == 5
-    /// The buffer MUST contain 0 values in positions 2..5
-    fn serialize_into(&self, buf: &mut [u8]) {
-        assert_eq!(buf.len(), 5, "serialize_into must be passed a buf of len 5");
-        match self {
-            Self::OnOff(x) => {
-                buf[0] = 43;
-                buf[1] = u8::from(*x);
-            }
-            Self::YesNo(x) => {
-                buf[0] = 44;
-                buf[1] = u8::from(*x);
-            }
-            Self::RASMode(x) => {
-                buf[0] = 45;
-                buf[1] = u8::from(*x);
-            }
-            Self::Mixer(x) => {
-                buf[0] = 47;
-                buf[1] = u8::from(*x);
+
+        impl $enum {
+            /// The IDs of all digital units, in ascending order.
+            pub const UNIT_IDS: &'static [u8] = &[$($id),*];
+
+            pub const fn unit_id(&self) -> u8 {
+                match self {
+                    $(Self::$name(_) => $id,)*
+                }
             }
-        };
-        // all other bits should be cleared
-        // but this is already satisfied, because we expect a null buffer
-    }
 
-    pub fn unit_id(&self) -> u8 {
-        match self {
-            Self::OnOff(_) => 43,
-            Self::YesNo(_) => 44,
-            Self::RASMode(_) => 45,
-            Self::Mixer(_) => 47,
+            /// Get the bool contained in this value, regardless of its unit.
+            pub const fn value(&self) -> bool {
+                match self {
+                    $(Self::$name(x))|* => *x,
+                }
+            }
         }
+    };
+}
+
+digital_units! {
+    /// Representation of all existing digital values representable in COE
+    #[repr(u8)]
+    #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum DigitalCOEValue {
+        OnOff(bool) = 43,
+        YesNo(bool) = 44,
+        RASMode(bool) = 45,
+        /// `true == Normal`,
+        /// `false == AUS`
+        Mixer(bool) = 47,
     }
 }
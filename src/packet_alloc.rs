@@ -1,6 +1,6 @@
 //! Implement [Packet] with the alloc feature enabled
 
-use self::packet_common::PacketIterator;
+use self::packet_common::{PacketIterMut, PacketIterator};
 
 use super::*;
 
@@ -15,8 +15,8 @@ use super::*;
 // u8, so no more then 255 (`u8::MAX`) bytes may ever be contained in a packets full representation.
 // The packet on wire contains 4 bytes of headers, leaving us with 251 usable bytes. A payload
 // length of 8 byte per payload yields 31 full payloads that fit in the max packet length.
+// serde is implemented by hand in serde_impls, serializing only the used payloads.
 #[derive(Hash, Debug, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Packet {
     /// CoE Version used. Currently, only 2.0 is supported.
     version: COEVersion,
@@ -27,28 +27,40 @@ impl TryFrom<&[u8]> for Packet {
     type Error = ParseCOEError;
     /// Try to parse this byteslice as a COE packet
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(value, false)
+    }
+}
+impl Packet {
+    /// Parse this byteslice as a COE packet, accepting [AnalogueCOEValue::Date]s that do not
+    /// exist in the calendar, like TA-Hardware does. See [Payload::try_from_permissive].
+    pub fn try_from_permissive(value: &[u8]) -> Result<Self, ParseCOEError> {
+        Self::parse(value, true)
+    }
+
+    /// Parse this byteslice as a COE packet, skipping date validation if `permissive`.
+    fn parse(value: &[u8], permissive: bool) -> Result<Self, ParseCOEError> {
         // the header must be four byte long
         if value.len() < 4 {
-            return Err(Self::Error::PacketBelowHeaderLength);
+            return Err(ParseCOEError::PacketBelowHeaderLength);
         };
         // parse the version number from the first two bytes
         let version: COEVersion = (value[0], value[1]).try_into()?;
         // assert that the packet length field is at most 31.
         if value[3] > 31 {
-            return Err(Self::Error::PacketLengthInconsistent(value[2], value[3]));
+            return Err(ParseCOEError::PacketLengthInconsistent(value[2], value[3]));
         } else {
             // assert that packet length and payload length are consistent
             if value[2]
                 != u8::try_from(4 + 8 * u16::from(value[3]))
                     .expect("Payload length should have been checked to be at most 31.")
             {
-                return Err(Self::Error::PacketLengthInconsistent(value[2], value[3]));
+                return Err(ParseCOEError::PacketLengthInconsistent(value[2], value[3]));
             }
         };
         // we are now certain that the header is correctly formed.
         // Assert that the packet actually has the correct size as given in the header.
-        if value.len() != value[2].into() {
-            return Err(Self::Error::PacketSizeConflictsWithHeader(
+        if value.len() != usize::from(value[2]) {
+            return Err(ParseCOEError::PacketSizeConflictsWithHeader(
                 value[2],
                 value.len(),
             ));
@@ -58,9 +70,10 @@ impl TryFrom<&[u8]> for Packet {
         let mut payloads: Vec<Payload> = vec![];
         for payload_nr in 0..value[3] {
             // each payload is exactly 8 bytes long - +4 is the header offset
-            payloads.push(
-                value[(payload_nr * 8 + 4) as usize..=(payload_nr * 8 + 11) as usize].try_into()?,
-            );
+            payloads.push(Payload::parse(
+                &value[(payload_nr * 8 + 4) as usize..=(payload_nr * 8 + 11) as usize],
+                permissive,
+            )?);
         }
         Ok(Packet { version, payloads })
     }
@@ -89,10 +102,10 @@ impl<'a> IntoIterator for &'a Packet {
     }
 }
 impl<'a> IntoIterator for &'a mut Packet {
-    type Item = &'a mut Payload;
-    type IntoIter = core::slice::IterMut<'a, Payload>;
+    type Item = PayloadMut<'a>;
+    type IntoIter = PacketIterMut<'a>;
     fn into_iter(self) -> Self::IntoIter {
-        self.payloads.iter_mut()
+        PacketIterMut::new(&mut self.payloads)
     }
 }
 impl Default for Packet {
@@ -100,12 +113,24 @@ impl Default for Packet {
         Self::new()
     }
 }
+/// Formats only the used payloads, like [Debug] would for the `alloc` implementation.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Packet {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "Packet {{ version: {}, payloads: {} }}",
+            self.version,
+            self.payloads.as_slice()
+        );
+    }
+}
 impl Packet {
     /// Create a packet without payloads
-    pub fn new() -> Packet {
+    pub const fn new() -> Packet {
         Packet {
-            version: COEVersion { major: 2, minor: 0 },
-            payloads: vec![],
+            version: COEVersion::V2_0,
+            payloads: Vec::new(),
         }
     }
 
@@ -133,47 +158,67 @@ impl Packet {
     }
 
     /// Get the COE Version of this Packet.
-    pub fn version(&self) -> COEVersion {
+    pub const fn version(&self) -> COEVersion {
         self.version
     }
 
     /// Get the payloads of this Packet by immutable reference
-    pub fn iter(&self) -> PacketIterator {
+    pub fn iter(&self) -> PacketIterator<'_> {
         PacketIterator::new(self)
     }
 
     /// Get the payloads of this Packet by mutable reference.
-    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Payload> {
-        self.payloads.iter_mut()
+    ///
+    /// Replacing a payload is checked like [Packet::try_push], see [PayloadMut].
+    pub fn iter_mut(&mut self) -> PacketIterMut<'_> {
+        PacketIterMut::new(&mut self.payloads)
     }
 
-    /// Create a [Packet] with [Payload]s. Fails if more then 31 payloads are given.
+    /// Create a [Packet] with [Payload]s.
+    ///
+    /// Fails if more then 31 payloads are given or one of them contains an invalid
+    /// [AnalogueCOEValue::Date].
     pub fn try_from_payloads(payloads: &[Payload]) -> Option<Packet> {
         let mut p = Packet::new();
         p.try_append_from_slice(payloads)?;
         Some(p)
     }
 
+    /// Create a [Packet] from at most 31 [Payload]s without validating their dates.
+    pub(crate) fn from_payloads_unchecked(payloads: &[Payload]) -> Packet {
+        debug_assert!(payloads.len() <= 31);
+        Packet {
+            version: COEVersion::V2_0,
+            payloads: payloads.to_vec(),
+        }
+    }
+
     /// Try to append a [Payload] to a [Packet]
     ///
-    /// Fails if the final packet size would exceed 255 bytes (31 payloads).
+    /// Fails if the final packet size would exceed 255 bytes (31 payloads) or the payload contains
+    /// an invalid [AnalogueCOEValue::Date].
     /// On failure, the packet was left unmodified.
     pub fn try_push(&mut self, payload: Payload) -> Option<()> {
         if (self.payloads.len() + 1) * 8 + 4 >= u8::MAX as usize {
             return None;
         };
+        payload.check().ok()?;
         self.payloads.push(payload);
         Some(())
     }
 
     /// Try to append all the given [Payload]s to a [Packet]
     ///
-    /// Fails if the final packet size would exceed 255 bytes (31 payloads).
+    /// Fails if the final packet size would exceed 255 bytes (31 payloads) or one of the payloads
+    /// contains an invalid [AnalogueCOEValue::Date].
     /// On failure, the packet was left unmodified.
     pub fn try_append_from_slice(&mut self, payloads: &[Payload]) -> Option<()> {
         if (self.payloads.len() + payloads.len()) * 8 + 4 >= u8::MAX as usize {
             return None;
         };
+        if payloads.iter().any(|p| p.check().is_err()) {
+            return None;
+        };
         self.payloads.extend_from_slice(payloads);
         Some(())
     }
@@ -21,3 +21,57 @@ impl<'a> Iterator for PacketIterator<'a> {
         }
     }
 }
+
+/// Mutable access to a [Payload] in a [Packet], obtained from [Packet::iter_mut].
+///
+/// Reading works through [Deref](core::ops::Deref). Replacing the payload is checked like
+/// [Packet::try_push], so that a [Packet] cannot be given an invalid
+/// [AnalogueCOEValue::Date](crate::AnalogueCOEValue) after it was built.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Packet, Payload};
+/// let value = COEValue::Analogue(AnalogueCOEValue::Watt(7));
+/// let mut packet = Packet::try_from_payloads(&[Payload::new(1, 0, value)]).unwrap();
+/// for mut payload in packet.iter_mut() {
+///     let moved = Payload::new(23, payload.pdo_index(), payload.value());
+///     assert_eq!(payload.try_set(moved), Some(()));
+///     let invalid = Payload::new(23, 0, AnalogueCOEValue::Date(30, 2, 2024).into());
+///     assert_eq!(payload.try_set(invalid), None);
+/// }
+/// assert_eq!(packet.iter().next(), Some(&Payload::new(23, 0, value)));
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct PayloadMut<'a>(&'a mut Payload);
+impl core::ops::Deref for PayloadMut<'_> {
+    type Target = Payload;
+    fn deref(&self) -> &Payload {
+        self.0
+    }
+}
+impl PayloadMut<'_> {
+    /// Replace the payload.
+    ///
+    /// Fails if the payload contains an invalid [AnalogueCOEValue::Date](crate::AnalogueCOEValue).
+    /// On failure, the packet was left unmodified.
+    pub fn try_set(&mut self, payload: Payload) -> Option<()> {
+        payload.check().ok()?;
+        *self.0 = payload;
+        Some(())
+    }
+}
+
+/// Iterates over the [Payload]s of a [Packet] by [PayloadMut].
+pub struct PacketIterMut<'a>(core::slice::IterMut<'a, Payload>);
+impl<'a> PacketIterMut<'a> {
+    pub fn new(payloads: &'a mut [Payload]) -> Self {
+        Self(payloads.iter_mut())
+    }
+}
+impl<'a> Iterator for PacketIterMut<'a> {
+    type Item = PayloadMut<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(PayloadMut)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
@@ -0,0 +1,121 @@
+//! Implementations of [proptest::arbitrary::Arbitrary], so that [any] generates only values that
+//! can be serialized and parsed again.
+//!
+//! Nodes are in 1-62, PDO indices in 0-63, [Packet]s contain at most 31 [Payload]s and every
+//! [AnalogueCOEValue::Date] exists in the calendar.
+
+use ::proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    prop_oneof,
+    sample::select,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+
+use crate::{
+    days_in_month,
+    generate::{ANALOGUE_UNIT_IDS, DATE_UNIT_ID, DIGITAL_UNIT_IDS},
+    AnalogueCOEValue, COEValue, DigitalCOEValue, Packet, Payload,
+};
+
+impl Arbitrary for AnalogueCOEValue {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        let date = (any::<u16>(), 1..=12_u8)
+            .prop_flat_map(|(year, month)| {
+                (
+                    1..=days_in_month(month, year).expect("month should be in 1-12"),
+                    Just(month),
+                    Just(year),
+                )
+            })
+            .prop_map(|(day, month, year)| AnalogueCOEValue::Date(day, month, year));
+        let other = (select(ANALOGUE_UNIT_IDS), any::<i32>())
+            .prop_filter("Dates are generated separately", |(unit_id, _)| {
+                *unit_id != DATE_UNIT_ID
+            })
+            .prop_map(|(unit_id, raw)| {
+                AnalogueCOEValue::from_raw(unit_id, raw)
+                    .expect("All units except Date accept every raw value")
+            });
+        // Date is one of 72 analogue units
+        prop_oneof![71 => other, 1 => date].boxed()
+    }
+}
+
+impl Arbitrary for DigitalCOEValue {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (select(DIGITAL_UNIT_IDS), any::<bool>())
+            .prop_map(|(unit_id, value)| {
+                DigitalCOEValue::try_from((&unit_id, &[u8::from(value), 0, 0, 0][..]))
+                    .expect("Digital units accept 0 and 1 as value")
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for COEValue {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            any::<AnalogueCOEValue>().prop_map(COEValue::Analogue),
+            any::<DigitalCOEValue>().prop_map(COEValue::Digital),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Payload {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (1..=62_u8, 0..=63_u8, any::<COEValue>())
+            .prop_map(|(node, pdo_index, value)| Payload::new(node, pdo_index, value))
+            .boxed()
+    }
+}
+
+impl Arbitrary for Packet {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<Payload>(), 0..=31)
+            .prop_map(|payloads| {
+                Packet::try_from_payloads(&payloads).expect("A Packet can hold 31 valid Payloads")
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::proptest::{prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn packet_roundtrip(packet in any::<Packet>()) {
+            let mut buf = [0_u8; 4 + 8 * 31];
+            let len = packet.try_serialize_into(&mut buf).unwrap();
+            prop_assert_eq!(len, packet.wire_size());
+            prop_assert_eq!(Packet::try_from(&buf[..len]), Ok(packet));
+        }
+
+        #[test]
+        fn payload_roundtrip(payload in any::<Payload>()) {
+            let packet = Packet::try_from_payloads(&[payload]).unwrap();
+            let mut buf = [0_u8; 4 + 8];
+            packet.try_serialize_into(&mut buf).unwrap();
+            prop_assert_eq!(Payload::try_from(&buf[4..]), Ok(payload));
+        }
+
+        #[test]
+        fn analogue_roundtrip(value in any::<AnalogueCOEValue>()) {
+            prop_assert_eq!(AnalogueCOEValue::from_raw(value.unit_id(), value.raw()), Ok(value));
+        }
+    }
+}
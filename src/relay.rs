@@ -0,0 +1,607 @@
+//! Forward values between CMIs with a rule engine.
+//!
+//! A [Relay] holds an ordered list of [Rule]s. Each received [Payload] is handled by the first
+//! rule matching it, which either drops it or forwards it to one or more [Target]s. Every target
+//! can rewrite node and PDO index and convert the value into another unit. Forwarded payloads are
+//! collected per destination and batched into full [Packet]s when flushing.
+//! Payloads not matched by any rule are dropped.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    net::{IpAddr, SocketAddr},
+    ops::RangeInclusive,
+};
+
+use crate::{COEValue, Format, Packet, Payload};
+
+/// The destination of a forwarded [Payload], together with the rewrites to apply.
+#[derive(Hash, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Target {
+    destination: SocketAddr,
+    node: Option<u8>,
+    pdo_offset: i8,
+    unit_id: Option<u8>,
+}
+impl Target {
+    /// Forward to `destination` without changing the payload.
+    pub fn new(destination: SocketAddr) -> Target {
+        Target {
+            destination,
+            node: None,
+            pdo_offset: 0,
+            unit_id: None,
+        }
+    }
+
+    /// Send to this node instead of the original one.
+    pub fn node(mut self, node: u8) -> Target {
+        self.node = Some(node);
+        self
+    }
+
+    /// Add `offset` to the PDO index.
+    ///
+    /// Payloads whose PDO index ends up outside of 0-63 are not forwarded.
+    pub fn pdo_offset(mut self, offset: i8) -> Target {
+        self.pdo_offset = offset;
+        self
+    }
+
+    /// Convert the value into the unit with ID `unit_id` (see
+    /// [AnalogueCOEValue::try_convert_to](crate::AnalogueCOEValue::try_convert_to)).
+    ///
+    /// Payloads that cannot be converted are not forwarded.
+    pub fn convert_to(mut self, unit_id: u8) -> Target {
+        self.unit_id = Some(unit_id);
+        self
+    }
+
+    /// The address payloads are forwarded to.
+    pub fn destination(&self) -> SocketAddr {
+        self.destination
+    }
+
+    /// Apply the rewrites to `payload`. Returns `None` if the result is not a valid payload or
+    /// cannot be added to a [Packet].
+    fn rewrite(&self, payload: &Payload) -> Option<Payload> {
+        let node = self.node.unwrap_or(payload.node());
+        if !(1..=62).contains(&node) {
+            return None;
+        };
+        let pdo_index = u8::try_from(i16::from(payload.pdo_index()) + i16::from(self.pdo_offset))
+            .ok()
+            .filter(|x| *x <= 63)?;
+        let value = match (self.unit_id, payload.value()) {
+            (None, value) => value,
+            (Some(unit_id), COEValue::Analogue(x)) => {
+                COEValue::Analogue(x.try_convert_to(unit_id).ok()?)
+            }
+            (Some(_), COEValue::Digital(_)) => return None,
+        };
+        let payload = Payload::new(node, pdo_index, value);
+        // payloads built by hand may contain dates that cannot be sent
+        payload.check().ok()?;
+        Some(payload)
+    }
+}
+
+/// What a [Rule] does with the payloads it matches.
+#[derive(Hash, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum Action {
+    Drop,
+    Forward(Vec<Target>),
+}
+
+/// Matches [Payload]s and decides what happens to them.
+///
+/// A new rule matches every payload and drops it. Restrict it with the matching methods and
+/// forward matched payloads with [Rule::forward]:
+/// ```rust
+/// # use coe::{Rule, Target};
+/// // send PDOs 0-15 of node 58 from 192.168.1.2 to node 12 on another CMI, with 1-16 on-wire
+/// let rule = Rule::new()
+///     .source("192.168.1.2".parse().unwrap())
+///     .node(58)
+///     .pdos(0..=15)
+///     .forward(Target::new("10.0.0.2:5442".parse().unwrap()).node(12).pdo_offset(1));
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rule {
+    source: Option<IpAddr>,
+    node: Option<u8>,
+    pdos: RangeInclusive<u8>,
+    format: Option<Format>,
+    unit_id: Option<u8>,
+    action: Action,
+}
+impl Default for Rule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Rule {
+    /// Create a rule matching every payload and dropping it.
+    pub fn new() -> Rule {
+        Rule {
+            source: None,
+            node: None,
+            pdos: 0..=63,
+            format: None,
+            unit_id: None,
+            action: Action::Drop,
+        }
+    }
+
+    /// Match only payloads received from `source`.
+    pub fn source(mut self, source: IpAddr) -> Rule {
+        self.source = Some(source);
+        self
+    }
+
+    /// Match only payloads sent to `node`.
+    pub fn node(mut self, node: u8) -> Rule {
+        self.node = Some(node);
+        self
+    }
+
+    /// Match only payloads with a PDO index in `pdos`.
+    pub fn pdos(mut self, pdos: RangeInclusive<u8>) -> Rule {
+        self.pdos = pdos;
+        self
+    }
+
+    /// Match only payloads with this [Format].
+    pub fn format(mut self, format: Format) -> Rule {
+        self.format = Some(format);
+        self
+    }
+
+    /// Match only payloads with the unit with ID `unit_id`.
+    pub fn unit(mut self, unit_id: u8) -> Rule {
+        self.unit_id = Some(unit_id);
+        self
+    }
+
+    /// Forward matched payloads to `target`.
+    ///
+    /// Call this multiple times to duplicate the payloads to multiple targets.
+    pub fn forward(mut self, target: Target) -> Rule {
+        match &mut self.action {
+            Action::Drop => self.action = Action::Forward(Vec::from([target])),
+            Action::Forward(targets) => targets.push(target),
+        };
+        self
+    }
+
+    /// Whether this rule matches `payload` received from `source`.
+    ///
+    /// IPv4 addresses mapped into IPv6 (as received on dual-stack sockets) match the IPv4 address.
+    pub fn matches(&self, source: IpAddr, payload: &Payload) -> bool {
+        accepts(self.source.map(|x| x.to_canonical()), source.to_canonical())
+            && accepts(self.node, payload.node())
+            && self.pdos.contains(&payload.pdo_index())
+            && accepts(self.format, payload.format())
+            && accepts(self.unit_id, payload.unit_id())
+    }
+}
+
+/// Whether `value` passes the optional `filter`.
+fn accepts<T: PartialEq>(filter: Option<T>, value: T) -> bool {
+    match filter {
+        Some(x) => x == value,
+        None => true,
+    }
+}
+
+/// Counters kept by a [Relay].
+#[derive(Hash, Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RelayStats {
+    /// Datagrams that were not valid CoE [Packet]s.
+    pub invalid_packets: u64,
+    /// Payloads that were dropped because no rule forwarded them.
+    pub dropped_payloads: u64,
+    /// Payloads that could not be rewritten, converted or packed for a target.
+    pub failed_payloads: u64,
+    /// Payloads queued for sending. Duplicated payloads are counted once per target.
+    pub forwarded_payloads: u64,
+}
+
+/// Routes [Payload]s according to [Rule]s and batches them into [Packet]s per destination.
+///
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Packet, Payload, Relay, Rule, Target};
+/// let cmi = "10.0.0.2:5442".parse().unwrap();
+/// let mut relay = Relay::new(Vec::from([
+///     // convert °C to K
+///     Rule::new().unit(1).forward(Target::new(cmi).convert_to(7)),
+/// ]));
+/// let packet = Packet::try_from_payloads(&[
+///     Payload::new(1, 0, COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215))),
+///     Payload::new(1, 1, COEValue::Analogue(AnalogueCOEValue::Watt(10))),
+/// ]).unwrap();
+/// relay.route("192.168.1.2".parse().unwrap(), &packet);
+///
+/// let sent = relay.flush();
+/// assert_eq!(sent.len(), 1);
+/// assert_eq!(sent[0].0, cmi);
+/// assert_eq!(
+///     sent[0].1.iter().next().unwrap().value(),
+///     COEValue::Analogue(AnalogueCOEValue::DegreeKelvin_Tens(2947)),
+/// );
+/// assert_eq!(relay.stats().dropped_payloads, 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Relay {
+    rules: Vec<Rule>,
+    pending: BTreeMap<SocketAddr, Vec<Payload>>,
+    stats: RelayStats,
+}
+impl Relay {
+    /// Create a relay with `rules`, which are tried in order.
+    pub fn new(rules: Vec<Rule>) -> Relay {
+        Relay {
+            rules,
+            pending: BTreeMap::new(),
+            stats: RelayStats::default(),
+        }
+    }
+
+    /// The rules of this relay.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// The counters of this relay.
+    pub fn stats(&self) -> RelayStats {
+        self.stats
+    }
+
+    /// Route all payloads in `packet`, received from `source`.
+    pub fn route(&mut self, source: IpAddr, packet: &Packet) {
+        for payload in packet.iter() {
+            self.route_payload(source, payload);
+        }
+    }
+
+    /// Route a single `payload`, received from `source`.
+    pub fn route_payload(&mut self, source: IpAddr, payload: &Payload) {
+        let targets = match self.rules.iter().find(|r| r.matches(source, payload)) {
+            Some(Rule {
+                action: Action::Forward(targets),
+                ..
+            }) => targets,
+            _ => {
+                self.stats.dropped_payloads += 1;
+                return;
+            }
+        };
+        for target in targets {
+            match target.rewrite(payload) {
+                Some(rewritten) => {
+                    self.pending
+                        .entry(target.destination)
+                        .or_default()
+                        .push(rewritten);
+                    self.stats.forwarded_payloads += 1;
+                }
+                None => self.stats.failed_payloads += 1,
+            };
+        }
+    }
+
+    /// Parse `datagram` received from `source` and route its payloads.
+    ///
+    /// Invalid datagrams are counted in [RelayStats::invalid_packets].
+    pub fn route_datagram(&mut self, source: IpAddr, datagram: &[u8]) {
+        match Packet::try_from(datagram) {
+            Ok(packet) => self.route(source, &packet),
+            Err(_) => self.stats.invalid_packets += 1,
+        };
+    }
+
+    /// Whether there are payloads waiting to be sent.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Take all queued payloads, batched into as few [Packet]s as possible per destination.
+    pub fn flush(&mut self) -> Vec<(SocketAddr, Packet)> {
+        let mut res = Vec::new();
+        for (destination, payloads) in core::mem::take(&mut self.pending) {
+            for chunk in payloads.chunks(31) {
+                // rewrite only queues valid payloads, so this should never fail
+                match Packet::try_from_payloads(chunk) {
+                    Some(packet) => res.push((destination, packet)),
+                    None => self.stats.failed_payloads += chunk.len() as u64,
+                };
+            }
+        }
+        res
+    }
+
+    /// Send all queued payloads from `socket`. Returns the number of packets sent.
+    ///
+    /// If sending fails, the payloads that were not sent stay queued for the next call.
+    #[cfg(feature = "std")]
+    pub fn send_pending(&mut self, socket: &std::net::UdpSocket) -> std::io::Result<usize> {
+        let packets = self.flush();
+        for (sent, (destination, packet)) in packets.iter().enumerate() {
+            if let Err(e) = socket.send_to(&packet.serialize_into_vec(), destination) {
+                for (destination, packet) in &packets[sent..] {
+                    self.pending
+                        .entry(*destination)
+                        .or_default()
+                        .extend(packet.iter().copied());
+                }
+                return Err(e);
+            };
+        }
+        Ok(packets.len())
+    }
+
+    /// Receive packets on `socket` and forward them according to the rules, forever.
+    ///
+    /// Payloads are collected for `batch_interval` after the first one arrives, so that values
+    /// from multiple received packets can share outgoing packets. With `Duration::ZERO`, every
+    /// received packet is forwarded immediately.
+    #[cfg(feature = "std")]
+    pub fn run(
+        &mut self,
+        socket: &std::net::UdpSocket,
+        batch_interval: std::time::Duration,
+    ) -> std::io::Result<()> {
+        use std::{io::ErrorKind, time::Instant};
+
+        let mut buf = [0_u8; 256];
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let timeout = match deadline {
+                Some(x) => {
+                    let remaining = x.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        self.send_pending(socket)?;
+                        deadline = None;
+                        continue;
+                    };
+                    Some(remaining)
+                }
+                None => None,
+            };
+            socket.set_read_timeout(timeout)?;
+            match socket.recv_from(&mut buf) {
+                Ok((length, source)) => {
+                    self.route_datagram(source.ip(), &buf[..length]);
+                    if batch_interval.is_zero() {
+                        self.send_pending(socket)?;
+                    } else if deadline.is_none() && self.has_pending() {
+                        deadline = Some(Instant::now() + batch_interval);
+                    };
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, DigitalCOEValue};
+
+    fn watt(node: u8, pdo_index: u8) -> Payload {
+        Payload::new(
+            node,
+            pdo_index,
+            COEValue::Analogue(AnalogueCOEValue::Watt(i32::from(pdo_index))),
+        )
+    }
+
+    fn ip(x: &str) -> IpAddr {
+        x.parse().unwrap()
+    }
+
+    fn addr(x: &str) -> SocketAddr {
+        x.parse().unwrap()
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut relay = Relay::new(Vec::from([
+            Rule::new().source(ip("10.0.0.1")).pdos(0..=1),
+            Rule::new()
+                .source(ip("10.0.0.1"))
+                .forward(Target::new(addr("10.0.0.9:5442")).node(2)),
+        ]));
+        for pdo in 0..4 {
+            relay.route_payload(ip("10.0.0.1"), &watt(1, pdo));
+            relay.route_payload(ip("10.0.0.2"), &watt(1, pdo));
+        }
+        let sent = relay.flush();
+        assert_eq!(
+            sent,
+            [(
+                addr("10.0.0.9:5442"),
+                Packet::try_from_payloads(&[watt(2, 2), watt(2, 3)]).unwrap()
+            )]
+        );
+        assert_eq!(
+            relay.stats(),
+            RelayStats {
+                invalid_packets: 0,
+                dropped_payloads: 6,
+                failed_payloads: 0,
+                forwarded_payloads: 2,
+            }
+        );
+        assert!(!relay.has_pending());
+    }
+
+    #[test]
+    fn duplicate_and_batch() {
+        let mut relay = Relay::new(Vec::from([Rule::new()
+            .format(Format::Analogue)
+            .forward(Target::new(addr("10.0.0.8:5442")))
+            .forward(Target::new(addr("10.0.0.9:5442")).pdo_offset(-10))]));
+        let packet =
+            Packet::try_from_payloads(&(0..31).map(|pdo| watt(1, pdo)).collect::<Vec<_>>())
+                .unwrap();
+        relay.route(ip("10.0.0.1"), &packet);
+        relay.route(ip("10.0.0.1"), &packet);
+        relay.route_payload(
+            ip("10.0.0.1"),
+            &Payload::new(1, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+        );
+        let sent = relay.flush();
+        // 62 payloads to the first target, 42 to the second
+        assert_eq!(
+            sent.iter()
+                .map(|(d, p)| (d.ip(), p.len()))
+                .collect::<Vec<_>>(),
+            [
+                (ip("10.0.0.8"), 31),
+                (ip("10.0.0.8"), 31),
+                (ip("10.0.0.9"), 31),
+                (ip("10.0.0.9"), 11),
+            ]
+        );
+        assert_eq!(
+            sent[2].1.iter().next(),
+            Some(&Payload::new(1, 0, watt(1, 10).value()))
+        );
+        assert_eq!(relay.stats().failed_payloads, 20);
+        assert_eq!(relay.stats().dropped_payloads, 1);
+    }
+
+    #[test]
+    fn convert() {
+        let mut relay = Relay::new(Vec::from([
+            Rule::new()
+                .unit(1)
+                .forward(Target::new(addr("10.0.0.9:5442")).convert_to(7)),
+            Rule::new().forward(Target::new(addr("10.0.0.9:5442")).convert_to(7)),
+        ]));
+        relay.route_payload(
+            ip("10.0.0.1"),
+            &Payload::new(
+                3,
+                4,
+                COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(-2731)),
+            ),
+        );
+        relay.route_payload(ip("10.0.0.1"), &watt(1, 1));
+        relay.route_payload(
+            ip("10.0.0.1"),
+            &Payload::new(1, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+        );
+        let sent = relay.flush();
+        assert_eq!(
+            sent[0].1.iter().copied().collect::<Vec<_>>(),
+            [Payload::new(
+                3,
+                4,
+                COEValue::Analogue(AnalogueCOEValue::DegreeKelvin_Tens(1))
+            )]
+        );
+        assert_eq!(relay.stats().failed_payloads, 2);
+    }
+
+    #[test]
+    fn invalid_date() {
+        let mut relay = Relay::new(Vec::from([
+            Rule::new().forward(Target::new(addr("10.0.0.9:5442")))
+        ]));
+        relay.route_payload(
+            ip("10.0.0.1"),
+            &Payload::new(
+                1,
+                0,
+                COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2024)),
+            ),
+        );
+        relay.route_payload(ip("10.0.0.1"), &watt(1, 1));
+        let sent = relay.flush();
+        assert_eq!(
+            sent,
+            [(
+                addr("10.0.0.9:5442"),
+                Packet::try_from_payloads(&[watt(1, 1)]).unwrap()
+            )]
+        );
+        assert_eq!(relay.stats().failed_payloads, 1);
+        assert_eq!(relay.stats().forwarded_payloads, 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn forward_over_udp() {
+        use std::net::UdpSocket;
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let relay_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut relay = Relay::new(Vec::from([Rule::new()
+            .node(58)
+            .forward(Target::new(receiver.local_addr().unwrap()).node(23))]));
+
+        let packet = Packet::try_from_payloads(&[watt(58, 1), watt(57, 2)]).unwrap();
+        relay.route_datagram(ip("127.0.0.1"), &packet.serialize_into_vec());
+        relay.route_datagram(ip("127.0.0.1"), &[2, 0, 4]);
+        assert_eq!(relay.send_pending(&relay_socket).unwrap(), 1);
+
+        let mut buf = [0_u8; 256];
+        let (length, _) = receiver.recv_from(&mut buf).unwrap();
+        assert_eq!(
+            Packet::try_from(&buf[..length]).unwrap(),
+            Packet::try_from_payloads(&[watt(23, 1)]).unwrap()
+        );
+        assert_eq!(relay.stats().invalid_packets, 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn failed_sends_stay_queued() {
+        use std::net::UdpSocket;
+
+        // an IPv4 socket cannot send to an IPv6 address
+        let relay_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut relay = Relay::new(Vec::from([Rule::new()
+            .forward(Target::new(addr("[::1]:5442")))
+            .forward(Target::new(addr("[::1]:5443")))]));
+        relay.route_payload(ip("10.0.0.1"), &watt(1, 1));
+        assert!(relay.send_pending(&relay_socket).is_err());
+        assert_eq!(
+            relay.flush(),
+            [
+                (
+                    addr("[::1]:5442"),
+                    Packet::try_from_payloads(&[watt(1, 1)]).unwrap()
+                ),
+                (
+                    addr("[::1]:5443"),
+                    Packet::try_from_payloads(&[watt(1, 1)]).unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ipv4_mapped_sources() {
+        let mapped = ip("::ffff:10.0.0.1");
+        assert!(Rule::new()
+            .source(ip("10.0.0.1"))
+            .matches(mapped, &watt(1, 1)));
+        assert!(Rule::new()
+            .source(mapped)
+            .matches(ip("10.0.0.1"), &watt(1, 1)));
+        assert!(!Rule::new()
+            .source(mapped)
+            .matches(ip("10.0.0.2"), &watt(1, 1)));
+    }
+}
@@ -0,0 +1,131 @@
+//! Implementations of [schemars::JsonSchema] for the types with a hand-written serde
+//! representation.
+//!
+//! [Payload], [COEVersion](crate::COEVersion) and the value enums derive their schemas.
+
+use ::alloc::borrow::Cow;
+use ::schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::{COEVersion, Packet, Payload, Readable};
+
+impl JsonSchema for Packet {
+    fn schema_name() -> Cow<'static, str> {
+        "Packet".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "description": "A CoE Packet containing at most 31 Payloads.",
+            "properties": {
+                "version": generator.subschema_for::<COEVersion>(),
+                "payloads": {
+                    "type": "array",
+                    "items": generator.subschema_for::<Payload>(),
+                    "maxItems": 31,
+                },
+            },
+            "required": ["version", "payloads"],
+        })
+    }
+}
+
+impl JsonSchema for Readable<Payload> {
+    fn schema_name() -> Cow<'static, str> {
+        "ReadablePayload".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "description": "A Payload with its value as shown by TA-Hardware.",
+            "properties": {
+                "node": {
+                    "description": "The CAN-ID of the virtual node sending the value.",
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 62,
+                },
+                "pdo": {
+                    "description": "The output index of the value on `node`, without the offset of 1 shown in the GUIs.",
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 63,
+                },
+                "unit_id": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 255,
+                },
+                "unit": {
+                    "description": "The symbol of the unit. Ignored when deserializing.",
+                    "type": "string",
+                },
+                "value": {
+                    "oneOf": [
+                        {
+                            "description": "The scaled value of an analogue unit.",
+                            "type": "number",
+                        },
+                        {
+                            "description": "The value of a digital unit.",
+                            "type": "boolean",
+                        },
+                        {
+                            "description": "The date of the unit with ID 62.",
+                            "type": "string",
+                            "pattern": "^[0-9]{4,5}-[0-9]{2}-[0-9]{2}$",
+                        },
+                    ],
+                },
+            },
+            "required": ["node", "pdo", "unit_id", "value"],
+        })
+    }
+}
+
+impl JsonSchema for Readable<Packet> {
+    fn schema_name() -> Cow<'static, str> {
+        "ReadablePacket".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "array",
+            "description": "The Payloads of a CoE Packet.",
+            "items": generator.subschema_for::<Readable<Payload>>(),
+            "maxItems": 31,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::schemars::schema_for;
+
+    #[test]
+    fn packet_schema() {
+        let schema = schema_for!(Packet);
+        assert_eq!(
+            schema.pointer("/properties/payloads/maxItems"),
+            Some(&31.into())
+        );
+        let payload = schema.pointer("/$defs/Payload/properties").unwrap();
+        assert_eq!(payload.pointer("/node/minimum"), Some(&1.into()));
+        assert_eq!(payload.pointer("/node/maximum"), Some(&62.into()));
+        assert_eq!(payload.pointer("/pdo_index/maximum"), Some(&63.into()));
+        assert!(schema.pointer("/$defs/AnalogueCOEValue").is_some());
+        assert!(schema.pointer("/$defs/DigitalCOEValue").is_some());
+    }
+
+    #[test]
+    fn readable_schema() {
+        let schema = schema_for!(Readable<Packet>);
+        assert_eq!(schema.pointer("/maxItems"), Some(&31.into()));
+        assert_eq!(
+            schema.pointer("/$defs/ReadablePayload/properties/pdo/maximum"),
+            Some(&63.into())
+        );
+    }
+}
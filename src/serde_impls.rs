@@ -0,0 +1,405 @@
+//! Serde representations of [Packet] and [Payload].
+//!
+//! [Packet]s serialize as their version and the used [Payload]s only, regardless of the `alloc`
+//! feature. Deserialization is validated just like parsing from bytes: nodes must be in 1-62, PDO
+//! indices in 0-63, [AnalogueCOEValue::Date]s must exist and a [Packet] holds at most 31
+//! [Payload]s.
+//!
+//! [Readable] wraps [Payload]s and [Packet]s for a human-friendly representation with scaled
+//! values and unit symbols.
+
+use ::serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::{SerializeSeq, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    date::DATE_UNIT_ID, AnalogueCOEValue, COEValue, COEVersion, DigitalCOEValue, Packet,
+    ParseCOEError, Payload,
+};
+
+/// The fields of a [COEVersion], validated when converting.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename = "COEVersion")]
+pub(crate) struct COEVersionRepr {
+    /// The major CoE Version. Only 2 is supported.
+    #[cfg_attr(feature = "schemars", schemars(range(min = 2, max = 2)))]
+    major: u8,
+    /// The minor CoE Version. Only 0 is supported.
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 0)))]
+    minor: u8,
+}
+impl TryFrom<COEVersionRepr> for COEVersion {
+    type Error = ParseCOEError;
+    fn try_from(value: COEVersionRepr) -> Result<Self, Self::Error> {
+        (value.major, value.minor).try_into()
+    }
+}
+
+/// The fields of a [Payload], validated when converting.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename = "Payload")]
+pub(crate) struct PayloadRepr {
+    /// The CAN-ID of the virtual node sending the value.
+    #[cfg_attr(feature = "schemars", schemars(range(min = 1, max = 62)))]
+    node: u8,
+    /// The output index of the value on `node`, without the offset of 1 shown in the GUIs.
+    #[cfg_attr(feature = "schemars", schemars(range(min = 0, max = 63)))]
+    pdo_index: u8,
+    value: COEValue,
+}
+impl TryFrom<PayloadRepr> for Payload {
+    type Error = ParseCOEError;
+    fn try_from(value: PayloadRepr) -> Result<Self, Self::Error> {
+        validated_payload(value.node, value.pdo_index, value.value)
+    }
+}
+
+/// Create a [Payload], applying the same checks as parsing it from bytes.
+fn validated_payload(node: u8, pdo_index: u8, value: COEValue) -> Result<Payload, ParseCOEError> {
+    if node == 0 || node >= 63 {
+        return Err(ParseCOEError::NodeDisallowed(node));
+    };
+    if pdo_index >= 64 {
+        return Err(ParseCOEError::PDOIndexDisallowed(pdo_index));
+    };
+    let payload = Payload::new(node, pdo_index, value);
+    payload.check().map_err(ParseCOEError::DateInvalid)?;
+    Ok(payload)
+}
+
+impl Serialize for Packet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Packet", 2)?;
+        state.serialize_field("version", &self.version())?;
+        state.serialize_field("payloads", &Payloads(self))?;
+        state.end()
+    }
+}
+
+/// Serialize the used [Payload]s of a [Packet] as a sequence.
+struct Payloads<'a>(&'a Packet);
+impl Serialize for Payloads<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for payload in self.0.iter() {
+            seq.serialize_element(payload)?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Packet")]
+struct PacketRepr {
+    // Only 2.0 is accepted, which is also the version of Packet::new
+    #[allow(dead_code)]
+    version: COEVersion,
+    payloads: PayloadList<Payload>,
+}
+impl<'de> Deserialize<'de> for Packet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(PacketRepr::deserialize(deserializer)?.payloads.0)
+    }
+}
+
+/// A [Packet] deserialized from a sequence of `T`, which can be converted into [Payload]s.
+struct PayloadList<T>(Packet, core::marker::PhantomData<T>);
+impl<'de, T> Deserialize<'de> for PayloadList<T>
+where
+    T: Deserialize<'de> + Into<Payload>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(PayloadListVisitor(core::marker::PhantomData))
+    }
+}
+struct PayloadListVisitor<T>(core::marker::PhantomData<T>);
+impl<'de, T> Visitor<'de> for PayloadListVisitor<T>
+where
+    T: Deserialize<'de> + Into<Payload>,
+{
+    type Value = PayloadList<T>;
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a sequence of at most 31 payloads")
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut packet = Packet::new();
+        while let Some(payload) = seq.next_element::<T>()? {
+            packet
+                .try_push(payload.into())
+                .ok_or_else(|| de::Error::invalid_length(32, &self))?;
+        }
+        Ok(PayloadList(packet, core::marker::PhantomData))
+    }
+}
+
+/// A human-friendly serde representation of a [Payload] or [Packet].
+///
+/// A [Payload] is represented as
+/// ```json
+/// {"node": 58, "pdo": 1, "unit_id": 1, "unit": "°C", "value": 21.5}
+/// ```
+/// where `value` is the value shown by TA-Hardware ([AnalogueCOEValue::scaled]), a bool for
+/// [DigitalCOEValue]s or a `YYYY-MM-DD` string for [AnalogueCOEValue::Date]. `unit` is ignored
+/// when deserializing. A [Packet] is represented as a sequence of [Payload]s.
+///
+/// This representation is meant for self-describing formats like JSON. Deserialization rounds
+/// `value` to the precision of the unit and is validated like parsing from bytes.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Payload, Readable};
+/// let payload = Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)));
+/// let json = serde_json::to_string(&Readable(payload)).unwrap();
+/// assert_eq!(json, r#"{"node":58,"pdo":1,"unit_id":1,"unit":"°C","value":21.5}"#);
+/// assert_eq!(serde_json::from_str::<Readable<Payload>>(&json).unwrap(), Readable(payload));
+/// ```
+#[derive(Hash, Debug, PartialEq, Clone, Copy)]
+pub struct Readable<T>(pub T);
+
+impl Serialize for Readable<Payload> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Payload", 5)?;
+        state.serialize_field("node", &self.0.node())?;
+        state.serialize_field("pdo", &self.0.pdo_index())?;
+        state.serialize_field("unit_id", &self.0.unit_id())?;
+        match self.0.value() {
+            COEValue::Analogue(AnalogueCOEValue::Date(day, month, year)) => {
+                state.serialize_field("unit", "")?;
+                state.serialize_field("value", &IsoDate(day, month, year))?;
+            }
+            COEValue::Analogue(value) => {
+                state.serialize_field("unit", value.unit_symbol())?;
+                state.serialize_field(
+                    "value",
+                    &value.scaled().expect("Only Date has no scaled value"),
+                )?;
+            }
+            COEValue::Digital(value) => {
+                state.serialize_field("unit", "")?;
+                state.serialize_field("value", &value.value())?;
+            }
+        };
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Payload")]
+struct ReadablePayloadRepr {
+    node: u8,
+    pdo: u8,
+    unit_id: u8,
+    value: ReadableValue,
+}
+impl<'de> Deserialize<'de> for Readable<Payload> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ReadablePayloadRepr::deserialize(deserializer)?;
+        let value = match repr.value {
+            ReadableValue::Bool(x) => COEValue::Digital(
+                DigitalCOEValue::try_from((&repr.unit_id, &[u8::from(x), 0, 0, 0][..]))
+                    .map_err(de::Error::custom)?,
+            ),
+            ReadableValue::Number(x) => COEValue::Analogue(
+                AnalogueCOEValue::from_scaled(repr.unit_id, x).map_err(de::Error::custom)?,
+            ),
+            ReadableValue::Date(day, month, year) => {
+                if repr.unit_id != DATE_UNIT_ID {
+                    return Err(de::Error::custom(format_args!(
+                        "Only the unit with ID {DATE_UNIT_ID} contains a date."
+                    )));
+                };
+                COEValue::Analogue(AnalogueCOEValue::Date(day, month, year))
+            }
+        };
+        Ok(Readable(
+            validated_payload(repr.node, repr.pdo, value).map_err(de::Error::custom)?,
+        ))
+    }
+}
+impl From<Readable<Payload>> for Payload {
+    fn from(value: Readable<Payload>) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Readable<Packet> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for payload in self.0.iter() {
+            seq.serialize_element(&Readable(*payload))?;
+        }
+        seq.end()
+    }
+}
+impl<'de> Deserialize<'de> for Readable<Packet> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Readable(
+            PayloadList::<Readable<Payload>>::deserialize(deserializer)?.0,
+        ))
+    }
+}
+
+/// Serializes day, month and year as `YYYY-MM-DD`.
+struct IsoDate(u8, u8, u16);
+impl core::fmt::Display for IsoDate {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.2, self.1, self.0)
+    }
+}
+impl Serialize for IsoDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The `value` of a [Readable] [Payload].
+enum ReadableValue {
+    Bool(bool),
+    Number(f64),
+    /// day, month, year
+    Date(u8, u8, u16),
+}
+impl<'de> Deserialize<'de> for ReadableValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ReadableValueVisitor)
+    }
+}
+struct ReadableValueVisitor;
+impl Visitor<'_> for ReadableValueVisitor {
+    type Value = ReadableValue;
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a bool, a number or a date formatted as YYYY-MM-DD")
+    }
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(ReadableValue::Bool(v))
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(ReadableValue::Number(v as f64))
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(ReadableValue::Number(v as f64))
+    }
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(ReadableValue::Number(v))
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let mut parts = v.split('-');
+        let mut next = || {
+            parts
+                .next()
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        };
+        let year = next()?
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))?;
+        let month = next()?
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))?;
+        let day = next()?
+            .parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))?;
+        if parts.next().is_some() {
+            return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+        };
+        Ok(ReadableValue::Date(day, month, year))
+    }
+}
+
+// serde_json needs alloc
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+
+    fn packet() -> Packet {
+        Packet::try_from_payloads(&[
+            Payload::new(
+                58,
+                1,
+                COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+            ),
+            Payload::new(3, 0, COEValue::Digital(DigitalCOEValue::OnOff(true))),
+            Payload::new(
+                3,
+                1,
+                COEValue::Analogue(AnalogueCOEValue::Date(17, 6, 2024)),
+            ),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn packet_only_used_payloads() {
+        let json = serde_json::to_string(&packet()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":{"major":2,"minor":0},"payloads":[{"node":58,"pdo_index":1,"value":{"Analogue":{"DegreeCentigrade_Tens":215}}},{"node":3,"pdo_index":0,"value":{"Digital":{"OnOff":true}}},{"node":3,"pdo_index":1,"value":{"Analogue":{"Date":[17,6,2024]}}}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Packet>(&json).unwrap(), packet());
+    }
+
+    #[test]
+    fn readable_packet() {
+        let json = serde_json::to_string(&Readable(packet())).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"node":58,"pdo":1,"unit_id":1,"unit":"°C","value":21.5},{"node":3,"pdo":0,"unit_id":43,"unit":"","value":true},{"node":3,"pdo":1,"unit_id":62,"unit":"","value":"2024-06-17"}]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Readable<Packet>>(&json).unwrap(),
+            Readable(packet())
+        );
+    }
+
+    #[test]
+    fn validated() {
+        // node out of range
+        let json = r#"{"node":0,"pdo_index":1,"value":{"Analogue":{"Watt":2}}}"#;
+        assert!(serde_json::from_str::<Payload>(json).is_err());
+        // pdo out of range
+        let json = r#"{"node":1,"pdo":64,"unit_id":69,"value":2}"#;
+        assert!(serde_json::from_str::<Readable<Payload>>(json).is_err());
+        // version 1.0
+        let json = r#"{"version":{"major":1,"minor":0},"payloads":[]}"#;
+        assert!(serde_json::from_str::<Packet>(json).is_err());
+        // wrong value for the unit
+        let json = r#"{"node":1,"pdo":1,"unit_id":43,"value":2}"#;
+        assert!(serde_json::from_str::<Readable<Payload>>(json).is_err());
+        let json = r#"{"node":1,"pdo":1,"unit_id":69,"value":"2024-01-01"}"#;
+        assert!(serde_json::from_str::<Readable<Payload>>(json).is_err());
+    }
+
+    #[test]
+    fn coded_values_as_codes() {
+        use crate::{Blinds, HeatingCircuitOpMode};
+        assert_eq!(serde_json::to_string(&Blinds::Down).unwrap(), "2");
+        assert_eq!(serde_json::from_str::<Blinds>("0").unwrap(), Blinds::Stop);
+        let unknown = serde_json::from_str::<HeatingCircuitOpMode>("7").unwrap();
+        assert_eq!(unknown, HeatingCircuitOpMode::from(7));
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), "7");
+    }
+
+    #[test]
+    fn validated_date() {
+        let json = r#"{"node":1,"pdo":1,"unit_id":62,"value":"2023-02-29"}"#;
+        assert!(serde_json::from_str::<Readable<Payload>>(json).is_err());
+    }
+
+    #[test]
+    fn at_most_31_payloads() {
+        let payloads = |count| {
+            let mut json = alloc::string::String::from("[");
+            for i in 0..count {
+                if i != 0 {
+                    json.push(',');
+                };
+                json.push_str(r#"{"node":1,"pdo":1,"unit_id":69,"value":2}"#);
+            }
+            json.push(']');
+            json
+        };
+        assert!(serde_json::from_str::<Readable<Packet>>(&payloads(31)).is_ok());
+        assert!(serde_json::from_str::<Readable<Packet>>(&payloads(32)).is_err());
+    }
+}
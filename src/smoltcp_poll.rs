@@ -0,0 +1,122 @@
+//! Drain and fill a smoltcp UDP [Socket] in a bare-metal poll loop.
+//!
+//! Neither function allocates: received [Packet]s are parsed directly from the socket buffer and
+//! outgoing [Packet]s are serialized directly into it.
+
+use smoltcp::{
+    socket::udp::{SendError, Socket, UdpMetadata},
+    wire::IpEndpoint,
+};
+
+use crate::{Packet, ParseCOEError};
+
+/// Parse every datagram queued in `socket` and pass it to `f` together with its sender.
+///
+/// Datagrams which are not valid CoE packets are passed to `f` as an error.
+/// Returns the number of datagrams removed from the socket.
+///
+/// Call this after every poll of the interface:
+/// ```rust,ignore
+/// loop {
+///     iface.poll(now(), &mut device, &mut sockets);
+///     let socket = sockets.get_mut::<Socket>(coe_handle);
+///     coe::receive_packets(socket, |packet, sender| {
+///         // handle the packet
+///     });
+///     coe::send_packet(socket, &outgoing, (cmi_address, 5442))?;
+/// }
+/// ```
+pub fn receive_packets<F>(socket: &mut Socket, mut f: F) -> usize
+where
+    F: FnMut(Result<Packet, ParseCOEError>, IpEndpoint),
+{
+    let mut count = 0;
+    while let Ok((data, meta)) = socket.recv() {
+        f(Packet::try_from(data), meta.endpoint);
+        count += 1;
+    }
+    count
+}
+
+/// Serialize `packet` into the transmit buffer of `socket`, addressed to `destination`.
+///
+/// The packet is sent on the next poll of the interface. Fails if the socket is not bound, the
+/// destination is unspecified or the transmit buffer is full, in which case nothing was
+/// enqueued.
+pub fn send_packet(
+    socket: &mut Socket,
+    packet: &Packet,
+    destination: impl Into<UdpMetadata>,
+) -> Result<(), SendError> {
+    let buf = socket.send(packet.wire_size(), destination)?;
+    packet
+        .try_serialize_into(buf)
+        .expect("The buffer has exactly the wire size of the packet");
+    Ok(())
+}
+
+// the loopback device needs a heap
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, Payload};
+    use smoltcp::{
+        iface::{Config, Interface, SocketSet},
+        phy::{Loopback, Medium},
+        socket::udp::{PacketBuffer, PacketMetadata},
+        time::Instant,
+        wire::{HardwareAddress, IpAddress, IpCidr},
+    };
+    use std::{vec, vec::Vec};
+
+    #[test]
+    fn send_and_receive() {
+        let mut device = Loopback::new(Medium::Ip);
+        let mut iface =
+            Interface::new(Config::new(HardwareAddress::Ip), &mut device, Instant::ZERO);
+        let address = IpAddress::v4(127, 0, 0, 1);
+        iface.update_ip_addrs(|addrs| addrs.push(IpCidr::new(address, 8)).unwrap());
+
+        let mut socket = Socket::new(
+            PacketBuffer::new(vec![PacketMetadata::EMPTY; 4], vec![0; 1024]),
+            PacketBuffer::new(vec![PacketMetadata::EMPTY; 4], vec![0; 1024]),
+        );
+        let destination = (address, 5442);
+        let packet = Packet::try_from_payloads(&[Payload::new(
+            58,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+        )])
+        .unwrap();
+        assert_eq!(
+            send_packet(&mut socket, &packet, destination),
+            Err(SendError::Unaddressable)
+        );
+        socket.bind(5442).unwrap();
+        send_packet(&mut socket, &packet, destination).unwrap();
+        socket.send_slice(&[2, 0], destination).unwrap();
+
+        let mut sockets = SocketSet::new(Vec::new());
+        let handle = sockets.add(socket);
+        for _ in 0..4 {
+            iface.poll(Instant::ZERO, &mut device, &mut sockets);
+        }
+
+        let mut received = Vec::new();
+        let count = receive_packets(sockets.get_mut(handle), |packet, sender| {
+            received.push((packet, sender))
+        });
+        assert_eq!(count, 2);
+        assert_eq!(
+            received,
+            [
+                (Ok(packet), destination.into()),
+                (
+                    Err(ParseCOEError::PacketBelowHeaderLength),
+                    destination.into()
+                )
+            ]
+        );
+        assert_eq!(receive_packets(sockets.get_mut(handle), |_, _| ()), 0);
+    }
+}
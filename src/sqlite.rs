@@ -0,0 +1,422 @@
+//! Record received values into SQLite and query their history.
+
+use std::{
+    path::Path,
+    string::String,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+    vec::Vec,
+};
+
+use ::rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{COEValue, Format, Packet, ParseCOEError, Payload};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS coe_values (
+    id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    node INTEGER NOT NULL,
+    pdo_index INTEGER NOT NULL,
+    format INTEGER NOT NULL,
+    unit_id INTEGER NOT NULL,
+    raw INTEGER NOT NULL,
+    scaled REAL
+);
+CREATE INDEX IF NOT EXISTS coe_values_signal
+    ON coe_values (source, node, pdo_index, format, timestamp);
+";
+
+/// The Errors that can occur when recording or querying values.
+#[derive(Debug)]
+pub enum RecorderError {
+    /// SQLite returned an error.
+    Sqlite(rusqlite::Error),
+    /// A timestamp was before the unix epoch or too far in the future.
+    TimestampOutOfRange(SystemTime),
+    /// A row in the database does not contain a valid [Payload].
+    InvalidRow(i64, ParseCOEError),
+}
+impl core::fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Sqlite(e) => write!(f, "SQLite error: {e}"),
+            Self::TimestampOutOfRange(x) => {
+                write!(f, "The timestamp {x:?} cannot be stored.")
+            }
+            Self::InvalidRow(id, e) => {
+                write!(
+                    f,
+                    "The row with id {id} does not contain a valid payload: {e}"
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for RecorderError {}
+impl From<rusqlite::Error> for RecorderError {
+    fn from(value: rusqlite::Error) -> Self {
+        Self::Sqlite(value)
+    }
+}
+
+/// A value read from the database.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    /// When the value was received, with millisecond precision.
+    pub timestamp: SystemTime,
+    /// The source the value was received from.
+    pub source: String,
+    /// The received value.
+    pub payload: Payload,
+}
+
+/// Records received [Payload]s into SQLite.
+///
+/// Every [Payload] is written into a single table:
+/// ```sql
+/// CREATE TABLE coe_values (
+///     id INTEGER PRIMARY KEY,
+///     -- milliseconds since the unix epoch
+///     timestamp INTEGER NOT NULL,
+///     -- usually the address of the sending CMI
+///     source TEXT NOT NULL,
+///     node INTEGER NOT NULL,
+///     pdo_index INTEGER NOT NULL,
+///     -- the on-wire format: 0 for digital, 1 for analogue
+///     format INTEGER NOT NULL,
+///     unit_id INTEGER NOT NULL,
+///     -- the on-wire value (0 or 1 for digital values)
+///     raw INTEGER NOT NULL,
+///     -- the value as shown by TA-Hardware, NULL for dates
+///     scaled REAL
+/// );
+/// CREATE INDEX coe_values_signal ON coe_values (source, node, pdo_index, format, timestamp);
+/// ```
+/// A signal is identified by source, node, PDO index and [Format], because analogue and digital
+/// outputs are numbered separately.
+///
+/// rusqlite links against the system SQLite. Enable the `bundled` feature of rusqlite in your own
+/// dependencies to compile SQLite instead.
+///
+/// ```rust
+/// # use coe::{AnalogueCOEValue, COEValue, Format, Payload, Recorder};
+/// use std::time::{Duration, UNIX_EPOCH};
+/// let recorder = Recorder::open_in_memory().unwrap();
+/// let payload = Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::Watt(10)));
+/// recorder.record(UNIX_EPOCH + Duration::from_secs(60), "cmi-1", &payload).unwrap();
+///
+/// let last = recorder.last_value("cmi-1", 58, 1, Format::Analogue).unwrap().unwrap();
+/// assert_eq!(last.payload, payload);
+/// ```
+pub struct Recorder {
+    connection: Connection,
+}
+impl Recorder {
+    /// Open (or create) the database at `path` and create the schema if required.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Recorder, RecorderError> {
+        Recorder::new(Connection::open(path)?)
+    }
+
+    /// Open a new in-memory database.
+    pub fn open_in_memory() -> Result<Recorder, RecorderError> {
+        Recorder::new(Connection::open_in_memory()?)
+    }
+
+    /// Use an existing connection and create the schema if required.
+    pub fn new(connection: Connection) -> Result<Recorder, RecorderError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Recorder { connection })
+    }
+
+    /// The underlying connection, e.g. for custom queries.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Record a single [Payload] received at `timestamp` from `source`.
+    pub fn record(
+        &self,
+        timestamp: SystemTime,
+        source: &str,
+        payload: &Payload,
+    ) -> Result<(), RecorderError> {
+        insert(&self.connection, to_millis(timestamp)?, source, payload)
+    }
+
+    /// Record all [Payload]s of a [Packet] received at `timestamp` from `source`.
+    ///
+    /// This uses a single transaction, so either all or none of the [Payload]s are recorded.
+    pub fn record_packet(
+        &mut self,
+        timestamp: SystemTime,
+        source: &str,
+        packet: &Packet,
+    ) -> Result<(), RecorderError> {
+        let timestamp = to_millis(timestamp)?;
+        let transaction = self.connection.transaction()?;
+        for payload in packet.iter() {
+            insert(&transaction, timestamp, source, payload)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The latest recorded value of a signal.
+    pub fn last_value(
+        &self,
+        source: &str,
+        node: u8,
+        pdo_index: u8,
+        format: Format,
+    ) -> Result<Option<Record>, RecorderError> {
+        let row = self
+            .connection
+            .query_row(
+                "SELECT id, timestamp, source, node, pdo_index, format, unit_id, raw
+                FROM coe_values
+                WHERE source = ?1 AND node = ?2 AND pdo_index = ?3 AND format = ?4
+                ORDER BY timestamp DESC, id DESC
+                LIMIT 1",
+                params![source, node, pdo_index, format_id(format)],
+                RawRow::from_row,
+            )
+            .optional()?;
+        row.map(RawRow::into_record).transpose()
+    }
+
+    /// All recorded values of a signal with `from <= timestamp < to`, oldest first.
+    pub fn history(
+        &self,
+        source: &str,
+        node: u8,
+        pdo_index: u8,
+        format: Format,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<Vec<Record>, RecorderError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, timestamp, source, node, pdo_index, format, unit_id, raw
+            FROM coe_values
+            WHERE source = ?1 AND node = ?2 AND pdo_index = ?3 AND format = ?4
+                AND timestamp >= ?5 AND timestamp < ?6
+            ORDER BY timestamp ASC, id ASC",
+        )?;
+        let rows = statement.query_map(
+            params![
+                source,
+                node,
+                pdo_index,
+                format_id(format),
+                to_millis(from)?,
+                to_millis(to)?
+            ],
+            RawRow::from_row,
+        )?;
+        rows.map(|row| row?.into_record()).collect()
+    }
+}
+
+/// Insert one row for `payload`.
+fn insert(
+    connection: &Connection,
+    timestamp: i64,
+    source: &str,
+    payload: &Payload,
+) -> Result<(), RecorderError> {
+    let (raw, scaled) = match payload.value() {
+        COEValue::Analogue(x) => (x.raw(), x.scaled()),
+        COEValue::Digital(x) => (i32::from(x.value()), Some(f64::from(u8::from(x.value())))),
+    };
+    connection
+        .prepare_cached(
+            "INSERT INTO coe_values (timestamp, source, node, pdo_index, format, unit_id, raw, scaled)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?
+        .execute(params![
+            timestamp,
+            source,
+            payload.node(),
+            payload.pdo_index(),
+            format_id(payload.format()),
+            payload.unit_id(),
+            raw,
+            scaled
+        ])?;
+    Ok(())
+}
+
+/// The on-wire ID of `format`.
+fn format_id(format: Format) -> u8 {
+    match format {
+        Format::Digital => 0,
+        Format::Analogue => 1,
+    }
+}
+
+fn to_millis(timestamp: SystemTime) -> Result<i64, RecorderError> {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|x| i64::try_from(x.as_millis()).ok())
+        .ok_or(RecorderError::TimestampOutOfRange(timestamp))
+}
+
+/// The columns of a row, before validation.
+struct RawRow {
+    id: i64,
+    timestamp: i64,
+    source: String,
+    node: u8,
+    pdo_index: u8,
+    format: u8,
+    unit_id: u8,
+    raw: i32,
+}
+impl RawRow {
+    fn from_row(row: &Row) -> rusqlite::Result<RawRow> {
+        Ok(RawRow {
+            id: row.get(0)?,
+            timestamp: row.get(1)?,
+            source: row.get(2)?,
+            node: row.get(3)?,
+            pdo_index: row.get(4)?,
+            format: row.get(5)?,
+            unit_id: row.get(6)?,
+            raw: row.get(7)?,
+        })
+    }
+
+    /// Validate the row by parsing its on-wire representation.
+    ///
+    /// Dates are not validated, since any [Payload] may be recorded, including those from
+    /// [Packet::try_from_permissive].
+    fn into_record(self) -> Result<Record, RecorderError> {
+        let mut bytes = [0_u8; 8];
+        bytes[0] = self.node;
+        bytes[1] = self.pdo_index;
+        bytes[2] = self.format;
+        bytes[3] = self.unit_id;
+        bytes[4..8].copy_from_slice(&self.raw.to_le_bytes());
+        let payload = Payload::try_from_permissive(&bytes)
+            .map_err(|e| RecorderError::InvalidRow(self.id, e))?;
+        // timestamps are written from SystemTimes after the epoch and are never negative
+        let millis = u64::try_from(self.timestamp).unwrap_or_default();
+        Ok(Record {
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            source: self.source,
+            payload,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, DigitalCOEValue};
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn watt(x: i32) -> Payload {
+        Payload::new(58, 1, COEValue::Analogue(AnalogueCOEValue::Watt(x)))
+    }
+
+    #[test]
+    fn history() {
+        let mut recorder = Recorder::open_in_memory().unwrap();
+        for i in 0..10 {
+            let packet = Packet::try_from_payloads(&[
+                watt(i),
+                Payload::new(58, 1, COEValue::Digital(DigitalCOEValue::OnOff(i % 2 == 0))),
+            ])
+            .unwrap();
+            recorder
+                .record_packet(at(i as u64), "cmi", &packet)
+                .unwrap();
+        }
+        recorder.record(at(3), "other", &watt(100)).unwrap();
+
+        let history = recorder
+            .history("cmi", 58, 1, Format::Analogue, at(2), at(5))
+            .unwrap();
+        assert_eq!(
+            history.iter().map(|r| r.payload).collect::<Vec<_>>(),
+            [watt(2), watt(3), watt(4)]
+        );
+        assert_eq!(history[0].timestamp, at(2));
+        assert_eq!(history[0].source, "cmi");
+
+        let last = recorder
+            .last_value("cmi", 58, 1, Format::Digital)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            last.payload.value(),
+            COEValue::Digital(DigitalCOEValue::OnOff(false))
+        );
+        assert_eq!(
+            recorder.last_value("cmi", 58, 2, Format::Analogue).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn scaled_column() {
+        let recorder = Recorder::open_in_memory().unwrap();
+        let payload = Payload::new(
+            1,
+            0,
+            COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+        );
+        recorder.record(at(0), "cmi", &payload).unwrap();
+        let (raw, scaled): (i32, f64) = recorder
+            .connection()
+            .query_row("SELECT raw, scaled FROM coe_values", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((raw, scaled), (215, 21.5));
+    }
+
+    #[test]
+    fn permissive_date() {
+        let mut recorder = Recorder::open_in_memory().unwrap();
+        let datagram = [2, 0, 12, 1, 58, 1, 1, 62, 30, 2, 0xe8, 0x07];
+        let packet = Packet::try_from_permissive(&datagram).unwrap();
+        recorder.record_packet(at(0), "cmi", &packet).unwrap();
+        let last = recorder
+            .last_value("cmi", 58, 1, Format::Analogue)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            last.payload.value(),
+            COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2024))
+        );
+        let history = recorder
+            .history("cmi", 58, 1, Format::Analogue, at(0), at(1))
+            .unwrap();
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn invalid_row() {
+        let recorder = Recorder::open_in_memory().unwrap();
+        recorder
+            .connection()
+            .execute(
+                "INSERT INTO coe_values (timestamp, source, node, pdo_index, format, unit_id, raw)
+                VALUES (0, 'cmi', 0, 1, 1, 69, 2)",
+                [],
+            )
+            .unwrap();
+        assert!(matches!(
+            recorder.last_value("cmi", 0, 1, Format::Analogue),
+            Err(RecorderError::InvalidRow(
+                1,
+                ParseCOEError::NodeDisallowed(0)
+            ))
+        ));
+    }
+}
@@ -1581,6 +1581,21 @@ fn packets_from_payloads() {
     assert_eq!(packets.len(), 3);
 }
 
+#[test]
+#[cfg(feature = "alloc")]
+fn try_packets_from_payloads() {
+    let valid = Payload::new(16, 8, AnalogueCOEValue::Date(29, 2, 2024).into());
+    let invalid = Payload::new(16, 8, AnalogueCOEValue::Date(29, 2, 2023).into());
+    let mut payloads = [valid; 40];
+    assert_eq!(
+        crate::try_packets_from_payloads(&payloads).map(|p| p.len()),
+        Some(2)
+    );
+    payloads[35] = invalid;
+    assert_eq!(crate::try_packets_from_payloads(&payloads), None);
+    assert_eq!(crate::packets_from_payloads(&payloads).len(), 2);
+}
+
 #[test]
 fn packet_iteration() {
     let raw_bytes = [
@@ -1611,3 +1626,436 @@ fn from_month_of_year_bounds() {
     assert_eq!(x, 12);
     assert_eq!(y, u16::MAX);
 }
+
+#[test]
+fn to_day_of_month_zero() {
+    assert_eq!(to_day_of_month(0, 5), Err(DateError::DayOutOfRange(0, 5)));
+    assert_eq!(to_day_of_month(5, 0), Err(DateError::MonthOutOfRange(0)));
+    assert_eq!(
+        to_month_of_year(0, 2024),
+        Err(DateError::MonthOutOfRange(0))
+    );
+}
+
+#[test]
+fn day_of_month_roundtrip() {
+    for month in 1..=12 {
+        for day in 1..=31 {
+            match to_day_of_month(day, month) {
+                Ok(val) => assert_eq!(from_day_of_month(val), Ok((day, month))),
+                Err(e) => assert_eq!(e, DateError::DayOutOfRange(day, month)),
+            }
+        }
+    }
+    assert_eq!(
+        from_day_of_month(to_day_of_month(29, 2).unwrap()),
+        Ok((29, 2))
+    );
+    assert!(from_day_of_month(AnalogueCOEValue::DayOfMonth(31 + 29)).is_err());
+}
+
+#[test]
+fn parse_value_date_invalid() {
+    let raw_bytes = [1, 1, 1, 62, 29, 2, 0xe7, 0x07];
+    let error: crate::ParseCOEError = TryInto::<crate::Payload>::try_into(&raw_bytes[0..8])
+        .expect_err("The 29th of february 2023 does not exist.");
+    assert_eq!(
+        error,
+        crate::ParseCOEError::DateInvalid(DateError::NotALeapYear(2023))
+    );
+}
+
+#[test]
+fn push_invalid_date() {
+    let payload = Payload::new(1, 1, AnalogueCOEValue::Date(31, 4, 2024).into());
+    let mut packet = Packet::new();
+    assert_eq!(packet.try_push(payload), None);
+    assert_eq!(packet.try_append_from_slice(&[payload]), None);
+    assert_eq!(Packet::try_from_payloads(&[payload]), None);
+    assert!(packet.is_empty());
+}
+
+#[test]
+fn permissive_dates() {
+    let raw_bytes = [0x02, 0x00, 12, 1, 1, 1, 1, 62, 30, 2, 0xe7, 0x07];
+    assert_eq!(
+        Packet::try_from(&raw_bytes[..]),
+        Err(crate::ParseCOEError::DateInvalid(DateError::DayOutOfRange(
+            30, 2
+        )))
+    );
+    let packet = Packet::try_from_permissive(&raw_bytes)
+        .expect("Permissive parsing accepts the 30th of february.");
+    let payload = *packet.iter().next().unwrap();
+    assert_eq!(
+        payload.value(),
+        COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2023))
+    );
+    // building packets stays strict
+    assert_eq!(Packet::new().try_push(payload), None);
+}
+
+#[test]
+fn raw_roundtrip() {
+    for unit_id in 0..=u8::MAX {
+        if let Ok(val) = AnalogueCOEValue::from_raw(unit_id, 0x0107_e502) {
+            assert_eq!(val.unit_id(), unit_id);
+            assert_eq!(val.raw(), 0x0107_e502);
+        }
+    }
+}
+
+#[test]
+fn convert_temperature() {
+    let val = AnalogueCOEValue::DegreeCentigrade_Tens(-2731);
+    assert_eq!(
+        val.try_convert_to(7),
+        Ok(AnalogueCOEValue::DegreeKelvin_Tens(1))
+    );
+    let val = AnalogueCOEValue::DegreeKelvin_Tens(0);
+    assert_eq!(
+        val.try_convert_to(1),
+        Ok(AnalogueCOEValue::DegreeCentigrade_Tens(-2732))
+    );
+}
+
+#[test]
+fn convert_volume_flow() {
+    let val = AnalogueCOEValue::CubicMeterPerHour(3);
+    assert_eq!(
+        val.try_convert_to(3),
+        Ok(AnalogueCOEValue::LiterPerHour(3000))
+    );
+    assert_eq!(
+        val.try_convert_to(22),
+        Ok(AnalogueCOEValue::LiterPerMinute(50))
+    );
+    assert_eq!(
+        val.try_convert_to(37),
+        Ok(AnalogueCOEValue::CubicMeterPerMinute(0))
+    );
+}
+
+#[test]
+fn convert_rounds_half_away_from_zero() {
+    let val = AnalogueCOEValue::Watt(-15);
+    assert_eq!(
+        val.try_convert_to(10),
+        Ok(AnalogueCOEValue::KiloWatt_Hundreds(-2))
+    );
+    let val = AnalogueCOEValue::Watt(14);
+    assert_eq!(
+        val.try_convert_to(10),
+        Ok(AnalogueCOEValue::KiloWatt_Hundreds(1))
+    );
+}
+
+#[test]
+fn convert_to_same_unit() {
+    let val = AnalogueCOEValue::Pascal(i32::MIN);
+    assert_eq!(val.try_convert_to(66), Ok(val));
+}
+
+#[test]
+fn convert_not_convertible() {
+    let val = AnalogueCOEValue::Time(12);
+    assert_eq!(
+        val.try_convert_to(5),
+        Err(ConvertUnitError::NotConvertible(60))
+    );
+    let val = AnalogueCOEValue::Minutes(12);
+    assert_eq!(
+        val.try_convert_to(60),
+        Err(ConvertUnitError::NotConvertible(60))
+    );
+    assert_eq!(
+        val.try_convert_to(200),
+        Err(ConvertUnitError::NotConvertible(200))
+    );
+}
+
+#[test]
+fn dimensions_are_analogue_units() {
+    for unit_id in 0..=u8::MAX {
+        if Dimension::of_unit(unit_id).is_some() {
+            assert!(AnalogueCOEValue::from_raw(unit_id, 0).is_ok());
+        }
+    }
+}
+
+#[test]
+fn coded_values_roundtrip() {
+    for raw in -2..6 {
+        let mode = HeatingCircuitOpMode::from(raw);
+        assert_eq!(i32::from(mode), raw);
+        let level = HeatingCircuitOpLevel::from(raw);
+        assert_eq!(i32::from(level), raw);
+        let blinds = Blinds::from(raw);
+        assert_eq!(i32::from(blinds), raw);
+    }
+    assert!(matches!(
+        HeatingCircuitOpMode::from(7),
+        HeatingCircuitOpMode::Unknown(code) if code.code() == 7
+    ));
+    // enum -> value -> enum is an identity for every code
+    for raw in -2..6 {
+        let mode = HeatingCircuitOpMode::from(raw);
+        let value = AnalogueCOEValue::from(mode);
+        assert_eq!(HeatingCircuitOpMode::try_from(value), Ok(mode));
+        let level = HeatingCircuitOpLevel::from(raw);
+        let value = AnalogueCOEValue::from(level);
+        assert_eq!(HeatingCircuitOpLevel::try_from(value), Ok(level));
+        let blinds = Blinds::from(raw);
+        assert_eq!(Blinds::try_from(AnalogueCOEValue::from(blinds)), Ok(blinds));
+    }
+    for b in [true, false] {
+        assert_eq!(bool::from(MixerMode::from(b)), b);
+        assert_eq!(bool::from(RasMode::from(b)), b);
+    }
+}
+
+#[test]
+fn coded_values_wrong_unit() {
+    assert_eq!(
+        HeatingCircuitOpMode::try_from(AnalogueCOEValue::HeatingCircuitOpLevel(1)),
+        Err(CodedValueError::WrongUnit(49))
+    );
+    assert_eq!(
+        BlindsPosition::try_from(AnalogueCOEValue::Blinds(1)),
+        Err(CodedValueError::WrongUnit(55))
+    );
+    assert_eq!(
+        MixerMode::try_from(DigitalCOEValue::RASMode(true)),
+        Err(CodedValueError::WrongUnit(45))
+    );
+}
+
+#[test]
+fn blinds_position() {
+    let pos = BlindsPosition::try_from(AnalogueCOEValue::BlindsPosition(100 + (30 << 8))).unwrap();
+    assert_eq!((pos.position(), pos.slat()), (100, 30));
+    assert_eq!(
+        AnalogueCOEValue::from(pos),
+        AnalogueCOEValue::BlindsPosition(100 + (30 << 8))
+    );
+    assert_eq!(
+        BlindsPosition::try_from(101),
+        Err(CodedValueError::ValueOutOfBounds(101))
+    );
+    assert_eq!(
+        BlindsPosition::try_from(1 << 16),
+        Err(CodedValueError::ValueOutOfBounds(1 << 16))
+    );
+    assert_eq!(BlindsPosition::new(0, 101), None);
+}
+
+#[test]
+fn time_roundtrip() {
+    for minutes in 0..24 * 60 {
+        let (hour, minute) = from_time(AnalogueCOEValue::Time(minutes)).unwrap();
+        assert_eq!(to_time(hour, minute), Ok(AnalogueCOEValue::Time(minutes)));
+        assert_eq!(to_colon(hour, minute), Ok(AnalogueCOEValue::Colon(minutes)));
+    }
+}
+
+#[test]
+fn time_out_of_bounds() {
+    assert_eq!(
+        from_time(AnalogueCOEValue::Time(-1)),
+        Err(FromTimeError::ValueOutOfBounds(-1))
+    );
+    assert_eq!(to_time(23, 60), Err(TimeError::MinuteOutOfRange(60)));
+    assert_eq!(TimeOfDay::from_minutes(24 * 60), None);
+}
+
+#[test]
+fn payload_short_slice() {
+    for len in 0..8 {
+        let raw_bytes = [0_u8; 8];
+        assert_eq!(
+            Payload::try_from(&raw_bytes[..len]),
+            Err(ParseCOEError::PayloadFrameLengthIncorrect(len))
+        );
+    }
+    let raw_bytes = [1_u8; 9];
+    assert_eq!(
+        Payload::try_from(&raw_bytes[..]),
+        Err(ParseCOEError::PayloadFrameLengthIncorrect(9))
+    );
+}
+
+#[test]
+fn packet_header_never_panics() {
+    let mut raw_bytes = [0_u8; 4 + 8 * 31];
+    raw_bytes[0] = 2;
+    for length in 0..=u8::MAX {
+        for payload_count in 0..=u8::MAX {
+            raw_bytes[2] = length;
+            raw_bytes[3] = payload_count;
+            for len in [4, 12, 4 + 8 * 31] {
+                let _ = Packet::try_from(&raw_bytes[..len]);
+            }
+        }
+    }
+    raw_bytes[3] = 32;
+    assert_eq!(
+        Packet::try_from(&raw_bytes[..4]),
+        Err(ParseCOEError::PacketLengthInconsistent(u8::MAX, 32))
+    );
+}
+
+#[test]
+fn every_unit_roundtrips() {
+    assert!(AnalogueCOEValue::UNIT_IDS.windows(2).all(|w| w[0] < w[1]));
+    for unit_id in (0..=u8::MAX).filter(|id| !AnalogueCOEValue::UNIT_IDS.contains(id)) {
+        assert_eq!(
+            AnalogueCOEValue::try_from((&unit_id, &[0, 0, 0, 0][..])),
+            Err(ParseCOEError::FormatAndUnitIncompatible(
+                Format::Analogue,
+                unit_id
+            ))
+        );
+    }
+    for unit_id in AnalogueCOEValue::UNIT_IDS.iter().copied() {
+        // the 1st of march 2024 is a valid date
+        let bytes = [1, 3, 0xe8, 0x07];
+        let value = AnalogueCOEValue::try_from((&unit_id, &bytes[..])).unwrap();
+        assert_eq!(value.unit_id(), unit_id);
+        assert_eq!(value.value_bytes(), bytes);
+        assert!(AnalogueCOEValue::meta(unit_id).is_some());
+    }
+}
+
+#[test]
+fn scaled_roundtrip() {
+    for unit_id in generate::ANALOGUE_UNIT_IDS.iter().copied() {
+        if unit_id == generate::DATE_UNIT_ID {
+            continue;
+        };
+        for raw in [i32::MIN, -1234567, -1, 0, 1, 215, i32::MAX] {
+            let val = AnalogueCOEValue::from_raw(unit_id, raw).unwrap();
+            let scaled = val.scaled().unwrap();
+            assert_eq!(AnalogueCOEValue::from_scaled(unit_id, scaled), Ok(val));
+        }
+    }
+}
+
+#[test]
+fn from_scaled_rounds() {
+    assert_eq!(
+        AnalogueCOEValue::from_scaled(23, 1.235),
+        Ok(AnalogueCOEValue::Bar_Hundreds(124))
+    );
+    assert_eq!(
+        AnalogueCOEValue::from_scaled(23, f64::NAN),
+        Err(ConvertUnitError::Overflow(23))
+    );
+    assert_eq!(
+        AnalogueCOEValue::from_scaled(43, 1.0),
+        Err(ConvertUnitError::NotConvertible(43))
+    );
+}
+
+/// defmt is meant for microcontrollers, where the firmware provides the logger. The host tests
+/// link against these stand-ins, which discard everything that is logged.
+#[cfg(feature = "defmt")]
+#[allow(unsafe_code)]
+mod defmt_host {
+    #[defmt::global_logger]
+    struct Logger;
+    // SAFETY: the logger has no state which could be accessed concurrently.
+    unsafe impl defmt::Logger for Logger {
+        fn acquire() {}
+        unsafe fn flush() {}
+        unsafe fn release() {}
+        unsafe fn write(_bytes: &[u8]) {}
+    }
+
+    defmt::timestamp!("{=u8}", 0);
+
+    #[defmt::panic_handler]
+    fn panic() -> ! {
+        panic!("defmt panic")
+    }
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+fn defmt_format_implemented() {
+    fn is_format<T: defmt::Format>() {}
+    is_format::<Packet>();
+    is_format::<Payload>();
+    is_format::<COEValue>();
+    is_format::<AnalogueCOEValue>();
+    is_format::<DigitalCOEValue>();
+    is_format::<COEVersion>();
+    is_format::<Format>();
+    is_format::<ParseCOEError>();
+    is_format::<DateError>();
+    is_format::<ConvertUnitError>();
+    is_format::<TimeOfDay>();
+    is_format::<BlindsPosition>();
+}
+
+#[test]
+fn const_construction() {
+    const VERSION: COEVersion = COEVersion::V2_0;
+    const PAYLOAD: Payload = Payload::new(
+        58,
+        1,
+        COEValue::Analogue(AnalogueCOEValue::Date(17, 6, 2024)),
+    );
+    const EMPTY: Packet = Packet::new();
+    const BUILDER: PacketBuilder = PacketBuilder::new()
+        .push(PAYLOAD)
+        .push(Payload::new(
+            1,
+            63,
+            COEValue::Digital(DigitalCOEValue::Mixer(true)),
+        ))
+        .push(Payload::new(
+            2,
+            0,
+            COEValue::Analogue(AnalogueCOEValue::Watt(-2)),
+        ));
+    const FRAME: [u8; BUILDER.wire_size()] = BUILDER.serialize();
+
+    assert_eq!(COEVersion::new(2, 0), Ok(VERSION));
+    assert_eq!(
+        COEVersion::new(1, 0),
+        Err(ParseCOEError::VersionNotImplemented(1, 0))
+    );
+    assert_eq!(EMPTY, Packet::default());
+    assert_eq!(BUILDER.len(), 3);
+    let packet = BUILDER.build();
+    let mut buf = [0_u8; 28];
+    assert_eq!(packet.try_serialize_into(&mut buf), Some(28));
+    assert_eq!(FRAME, buf);
+    assert_eq!(Packet::try_from(&FRAME[..]), Ok(packet));
+    assert_eq!(PacketBuilder::new().serialize(), [2, 0, 4, 0]);
+}
+
+#[test]
+fn builder_limits() {
+    let payload = Payload::new(1, 0, COEValue::Digital(DigitalCOEValue::OnOff(false)));
+    let mut builder = PacketBuilder::new();
+    for _ in 0..31 {
+        builder = builder.push(payload);
+    }
+    assert_eq!(builder.try_push(payload), None);
+    assert_eq!(builder.build().len(), 31);
+    let invalid = Payload::new(
+        1,
+        0,
+        COEValue::Analogue(AnalogueCOEValue::Date(30, 2, 2024)),
+    );
+    assert_eq!(PacketBuilder::new().try_push(invalid), None);
+    let value = COEValue::Digital(DigitalCOEValue::OnOff(false));
+    for (node, pdo_index) in [(0, 0), (63, 0), (1, 64)] {
+        let invalid = Payload::new(node, pdo_index, value);
+        assert_eq!(PacketBuilder::new().try_push(invalid), None);
+    }
+    assert!(PacketBuilder::new()
+        .try_push(Payload::new(62, 63, value))
+        .is_some());
+}
@@ -0,0 +1,199 @@
+//! Conversions between the date and time units and [time].
+//!
+//! A [Date] converts into [AnalogueCOEValue::Date], or into [DayOfMonth] and [MonthOfYear]
+//! for [AnalogueCOEValue::DayOfMonth] and [AnalogueCOEValue::MonthOfYear]. Only
+//! [AnalogueCOEValue::Date] and [AnalogueCOEValue::Time] carry enough information to be
+//! converted back into a [Date] or [Time].
+
+use ::time::{error::ComponentRange, Date, Month, Time};
+
+use crate::{AnalogueCOEValue, DateTimeConversionError, DayOfMonth, MonthOfYear};
+
+/// Convert a [Date] into [AnalogueCOEValue::Date].
+///
+/// Fails if the year is not in `0..=65535`.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use time::{Date, Month};
+/// let date = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+/// assert_eq!(AnalogueCOEValue::try_from(date), Ok(AnalogueCOEValue::Date(29, 2, 2024)));
+/// ```
+impl TryFrom<Date> for AnalogueCOEValue {
+    type Error = DateTimeConversionError;
+    fn try_from(value: Date) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year()))?;
+        Ok(AnalogueCOEValue::Date(
+            value.day(),
+            u8::from(value.month()),
+            year,
+        ))
+    }
+}
+
+/// Convert a [Date] into its [DayOfMonth], dropping the year.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DayOfMonth};
+/// use time::{Date, Month};
+/// let today = Date::from_calendar_date(2024, Month::June, 17).unwrap();
+/// let val = AnalogueCOEValue::from(DayOfMonth::from(today));
+/// assert_eq!(val, AnalogueCOEValue::DayOfMonth(171));
+/// ```
+impl From<Date> for DayOfMonth {
+    fn from(value: Date) -> Self {
+        DayOfMonth::new(value.day(), u8::from(value.month()))
+            .expect("A Date exists in the calendar")
+    }
+}
+
+/// Convert a [Date] into its [MonthOfYear], dropping the day.
+///
+/// Fails if the year is not in `0..=65535`.
+impl TryFrom<Date> for MonthOfYear {
+    type Error = DateTimeConversionError;
+    fn try_from(value: Date) -> Result<Self, Self::Error> {
+        let year = u16::try_from(value.year())
+            .map_err(|_| DateTimeConversionError::YearOutOfRange(value.year()))?;
+        Ok(MonthOfYear::new(u8::from(value.month()), year).expect("Month is in 1..=12"))
+    }
+}
+
+/// Convert [AnalogueCOEValue::Date] into a [Date].
+///
+/// Fails for other units, for dates which do not exist in the calendar and for years [time]
+/// cannot represent (after 9999 without its `large-dates` feature).
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use time::{Date, Month};
+/// let date = Date::try_from(AnalogueCOEValue::Date(17, 6, 2024));
+/// assert_eq!(date, Ok(Date::from_calendar_date(2024, Month::June, 17).unwrap()));
+///
+/// let date = Date::try_from(AnalogueCOEValue::Date(30, 2, 2024));
+/// assert_eq!(date, Err(DateTimeConversionError::InvalidDate(30, 2, 2024)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for Date {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Date(days, months, years) => {
+                let month = Month::try_from(months)
+                    .map_err(|_| DateTimeConversionError::InvalidDate(days, months, years))?;
+                Date::from_calendar_date(i32::from(years), month, days).map_err(
+                    |e: ComponentRange| {
+                        if e.name() == "year" {
+                            DateTimeConversionError::YearOutOfRange(i32::from(years))
+                        } else {
+                            DateTimeConversionError::InvalidDate(days, months, years)
+                        }
+                    },
+                )
+            }
+            _ => Err(DateTimeConversionError::NotDate),
+        }
+    }
+}
+
+/// Convert a [Time] into [AnalogueCOEValue::Time].
+///
+/// CoE only transmits hours and minutes, seconds are truncated.
+/// ```rust
+/// # use coe::AnalogueCOEValue;
+/// use time::Time;
+/// let time = Time::from_hms(13, 37, 59).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(time), AnalogueCOEValue::Time(13 * 60 + 37));
+/// ```
+impl From<Time> for AnalogueCOEValue {
+    fn from(value: Time) -> Self {
+        AnalogueCOEValue::Time(i32::from(value.hour()) * 60 + i32::from(value.minute()))
+    }
+}
+
+/// Convert [AnalogueCOEValue::Time] into a [Time].
+///
+/// Fails for other units and for values outside of 00:00-23:59.
+/// ```rust
+/// # use coe::{AnalogueCOEValue, DateTimeConversionError};
+/// use time::Time;
+/// let time = Time::try_from(AnalogueCOEValue::Time(13 * 60 + 37));
+/// assert_eq!(time, Ok(Time::from_hms(13, 37, 0).unwrap()));
+///
+/// let time = Time::try_from(AnalogueCOEValue::Time(24 * 60));
+/// assert_eq!(time, Err(DateTimeConversionError::InvalidTime(24 * 60)));
+/// ```
+impl TryFrom<AnalogueCOEValue> for Time {
+    type Error = DateTimeConversionError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Time(x) => {
+                if !(0..24 * 60).contains(&x) {
+                    return Err(DateTimeConversionError::InvalidTime(x));
+                };
+                Time::from_hms(
+                    (x / 60).try_into().expect("x / 60 is in 0..24"),
+                    (x % 60).try_into().expect("Modulo 60 yields u8"),
+                    0,
+                )
+                .map_err(|_| DateTimeConversionError::InvalidTime(x))
+            }
+            _ => Err(DateTimeConversionError::NotTime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn date_roundtrip() {
+        let date = Date::from_calendar_date(1999, Month::December, 31).unwrap();
+        let val = AnalogueCOEValue::try_from(date).unwrap();
+        assert_eq!(val, AnalogueCOEValue::Date(31, 12, 1999));
+        assert_eq!(Date::try_from(val), Ok(date));
+    }
+
+    #[test]
+    fn date_rejects_invalid_month() {
+        assert_eq!(
+            Date::try_from(AnalogueCOEValue::Date(1, 13, 2023)),
+            Err(DateTimeConversionError::InvalidDate(1, 13, 2023))
+        );
+        assert_eq!(
+            Date::try_from(AnalogueCOEValue::Date(29, 2, 2023)),
+            Err(DateTimeConversionError::InvalidDate(29, 2, 2023))
+        );
+    }
+
+    #[test]
+    fn date_year_out_of_range() {
+        // the same error as for jiff, unless time is built with large-dates
+        if Date::MAX.year() < 10_000 {
+            assert_eq!(
+                Date::try_from(AnalogueCOEValue::Date(1, 1, 10_000)),
+                Err(DateTimeConversionError::YearOutOfRange(10_000))
+            );
+        };
+    }
+
+    #[test]
+    fn day_and_month() {
+        let date = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+        assert_eq!(DayOfMonth::from(date), DayOfMonth::new(29, 2).unwrap());
+        assert_eq!(
+            MonthOfYear::try_from(date),
+            Ok(MonthOfYear::new(2, 2024).unwrap())
+        );
+    }
+
+    #[test]
+    fn time_bounds() {
+        assert_eq!(
+            Time::try_from(AnalogueCOEValue::Time(-1)),
+            Err(DateTimeConversionError::InvalidTime(-1))
+        );
+        assert_eq!(
+            Time::try_from(AnalogueCOEValue::Time(24 * 60 - 1)),
+            Ok(Time::from_hms(23, 59, 0).unwrap())
+        );
+    }
+}
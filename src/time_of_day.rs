@@ -0,0 +1,195 @@
+//! Helpers for the units containing a time of day.
+//!
+//! [AnalogueCOEValue::Time] and [AnalogueCOEValue::Colon] both contain the minutes since
+//! midnight, which TA-Hardware shows as `HH:MM`. TA-Hardware accepts any value in these units;
+//! the helpers in this module only create and accept values in 00:00-23:59.
+
+use crate::AnalogueCOEValue;
+
+/// The Errors that can occur when validating a time of day.
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeError {
+    /// The hour is not in 0-23.
+    HourOutOfRange(u8),
+    /// The minute is not in 0-59.
+    MinuteOutOfRange(u8),
+}
+impl core::fmt::Display for TimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::HourOutOfRange(x) => {
+                write!(f, "The hour must be in 0-23, but {x} was supplied.")
+            }
+            Self::MinuteOutOfRange(x) => {
+                write!(f, "The minute must be in 0-59, but {x} was supplied.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for TimeError {}
+
+/// The Errors that can occur when reading a time of day.
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FromTimeError {
+    /// The supplied AnalogueCOEValue was neither Time nor Colon.
+    NotTime,
+    /// The value is not in 00:00-23:59.
+    /// Note that TA-Hardware always accepts the Value, but shows useless values here instead.
+    ValueOutOfBounds(i32),
+}
+impl core::fmt::Display for FromTimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotTime => write!(f, "Value was neither Time nor Colon"),
+            Self::ValueOutOfBounds(x) => {
+                write!(f, "The Value {x} cannot be parsed as a time of day.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for FromTimeError {}
+
+/// A validated time of day with minute precision, as transmitted by [AnalogueCOEValue::Time]
+/// and [AnalogueCOEValue::Colon].
+///
+/// ```rust
+/// # use coe::{AnalogueCOEValue, TimeOfDay};
+/// let time = TimeOfDay::new(6, 30).unwrap();
+/// assert_eq!(AnalogueCOEValue::from(time), AnalogueCOEValue::Time(390));
+/// assert_eq!(TimeOfDay::try_from(AnalogueCOEValue::Colon(390)), Ok(time));
+/// assert_eq!(format!("{time}"), "06:30");
+/// ```
+#[derive(Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+impl TimeOfDay {
+    /// Create a time of day from hour (0-23) and minute (0-59).
+    pub fn new(hour: u8, minute: u8) -> Result<TimeOfDay, TimeError> {
+        if hour > 23 {
+            return Err(TimeError::HourOutOfRange(hour));
+        };
+        if minute > 59 {
+            return Err(TimeError::MinuteOutOfRange(minute));
+        };
+        Ok(TimeOfDay { hour, minute })
+    }
+
+    /// Create a time of day from the minutes since midnight.
+    ///
+    /// Returns `None` if `minutes` is not in `0..24 * 60`.
+    pub fn from_minutes(minutes: i32) -> Option<TimeOfDay> {
+        if !(0..24 * 60).contains(&minutes) {
+            return None;
+        };
+        Some(TimeOfDay {
+            hour: (minutes / 60)
+                .try_into()
+                .expect("minutes / 60 is at most 23"),
+            minute: (minutes % 60).try_into().expect("Modulo 60 yields u8"),
+        })
+    }
+
+    /// The hour (0-23)
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// The minute (0-59)
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The minutes since midnight, as transmitted on-wire.
+    pub fn minutes(&self) -> i32 {
+        i32::from(self.hour) * 60 + i32::from(self.minute)
+    }
+}
+impl core::fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+/// Convert into [AnalogueCOEValue::Time].
+impl From<TimeOfDay> for AnalogueCOEValue {
+    fn from(value: TimeOfDay) -> Self {
+        AnalogueCOEValue::Time(value.minutes())
+    }
+}
+/// Read [AnalogueCOEValue::Time] or [AnalogueCOEValue::Colon].
+impl TryFrom<AnalogueCOEValue> for TimeOfDay {
+    type Error = FromTimeError;
+    fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+        match value {
+            AnalogueCOEValue::Time(x) | AnalogueCOEValue::Colon(x) => {
+                TimeOfDay::from_minutes(x).ok_or(FromTimeError::ValueOutOfBounds(x))
+            }
+            _ => Err(FromTimeError::NotTime),
+        }
+    }
+}
+
+/// Convert hour and minute into [AnalogueCOEValue::Time].
+///
+/// Fails when `hour` is not in 0-23 or `minute` is not in 0-59.
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, TimeError, to_time};
+/// let val = to_time(13, 37);
+/// assert_eq!(val, Ok(AnalogueCOEValue::Time(817)));
+///
+/// let val = to_time(24, 0);
+/// assert_eq!(val, Err(TimeError::HourOutOfRange(24)));
+/// ```
+pub fn to_time(hour: u8, minute: u8) -> Result<AnalogueCOEValue, TimeError> {
+    Ok(TimeOfDay::new(hour, minute)?.into())
+}
+
+/// Convert hour and minute into [AnalogueCOEValue::Colon].
+///
+/// Fails when `hour` is not in 0-23 or `minute` is not in 0-59.
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, TimeError, to_colon};
+/// let val = to_colon(13, 37);
+/// assert_eq!(val, Ok(AnalogueCOEValue::Colon(817)));
+///
+/// let val = to_colon(12, 60);
+/// assert_eq!(val, Err(TimeError::MinuteOutOfRange(60)));
+/// ```
+pub fn to_colon(hour: u8, minute: u8) -> Result<AnalogueCOEValue, TimeError> {
+    Ok(AnalogueCOEValue::Colon(
+        TimeOfDay::new(hour, minute)?.minutes(),
+    ))
+}
+
+/// Convert [AnalogueCOEValue::Time] or [AnalogueCOEValue::Colon] into hour and minute.
+///
+/// Example:
+/// ```rust
+/// # use coe::{AnalogueCOEValue, from_time, FromTimeError};
+/// let val = from_time(AnalogueCOEValue::Time(817));
+/// assert_eq!(val, Ok((13, 37)));
+///
+/// let val = from_time(AnalogueCOEValue::Colon(24 * 60));
+/// assert_eq!(val, Err(FromTimeError::ValueOutOfBounds(24 * 60)));
+///
+/// let val = from_time(AnalogueCOEValue::DegreeKelvin_Tens(123));
+/// assert_eq!(val, Err(FromTimeError::NotTime));
+/// ```
+// Not a method on AnalogueCOEValue for the same reason as from_day_of_month.
+pub fn from_time(value: AnalogueCOEValue) -> Result<(u8, u8), FromTimeError> {
+    let time = TimeOfDay::try_from(value)?;
+    Ok((time.hour(), time.minute()))
+}
@@ -0,0 +1,466 @@
+//! Tunnel [Packet]s across untrusted networks inside an authenticated, encrypted envelope.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+    vec::Vec,
+};
+
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, OsRng, Payload as AeadPayload},
+    KeyInit, XChaCha20Poly1305, XNonce,
+};
+
+use crate::{Packet, ParseCOEError};
+
+/// The version of the envelope format.
+const VERSION: u8 = 1;
+/// Length of the authenticated, unencrypted header.
+const HEADER_LENGTH: usize = 1 + 16 + 8 + 8;
+/// Length of the Poly1305 tag.
+const TAG_LENGTH: usize = 16;
+/// The number of counters before the highest one a session remembers.
+const WINDOW: u64 = 64;
+
+/// The Errors that can occur when sealing or opening an envelope.
+#[derive(Debug)]
+pub enum TunnelError {
+    /// Sending or receiving on a socket failed.
+    Io(io::Error),
+    /// The envelope is shorter then header and tag.
+    EnvelopeTooShort(usize),
+    /// The envelope version is not supported.
+    VersionNotSupported(u8),
+    /// The envelope was sent at this time (milliseconds since the unix epoch), which is further
+    /// from the current time then the maximum age.
+    Stale(u64),
+    /// The counter of the envelope was already seen in its session or is too old.
+    Replayed(u64),
+    /// The envelope was not sealed with the same key or was modified.
+    AuthenticationFailed,
+    /// The envelope was authentic, but does not contain a valid CoE packet.
+    InvalidPacket(ParseCOEError),
+    /// The sender has used up all counters of its session.
+    CounterExhausted,
+    /// The system time is before the unix epoch.
+    TimeBeforeEpoch,
+}
+impl core::fmt::Display for TunnelError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO Error: {e}"),
+            Self::EnvelopeTooShort(x) => {
+                write!(
+                    f,
+                    "The envelope is {x} bytes long, which is shorter then header and tag."
+                )
+            }
+            Self::VersionNotSupported(x) => {
+                write!(f, "The envelope version {x} is not supported.")
+            }
+            Self::Stale(x) => write!(f, "The envelope sent at {x} ms is too old."),
+            Self::Replayed(x) => write!(f, "The envelope with counter {x} was replayed."),
+            Self::AuthenticationFailed => write!(f, "The envelope could not be authenticated."),
+            Self::InvalidPacket(e) => write!(f, "The envelope contains an invalid packet: {e}"),
+            Self::CounterExhausted => write!(f, "The session has used up all counters."),
+            Self::TimeBeforeEpoch => write!(f, "The system time is before the unix epoch."),
+        }
+    }
+}
+impl std::error::Error for TunnelError {}
+impl From<io::Error> for TunnelError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+fn millis_since_epoch(now: SystemTime) -> Result<u64, TunnelError> {
+    now.duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|x| u64::try_from(x.as_millis()).ok())
+        .ok_or(TunnelError::TimeBeforeEpoch)
+}
+
+fn nonce(session: &[u8], counter: &[u8]) -> XNonce {
+    let mut nonce = XNonce::default();
+    nonce[..16].copy_from_slice(session);
+    nonce[16..].copy_from_slice(counter);
+    nonce
+}
+
+/// Seals [Packet]s into envelopes for a [TunnelReceiver].
+///
+/// CoE has no authentication at all, so CMIs must never be reachable from untrusted networks.
+/// To forward values between sites, a [TunnelSender] seals plain CoE [Packet]s with a pre-shared
+/// 32 byte key and a [TunnelReceiver] on the other site opens them again and forwards them as plain
+/// CoE on its local network.
+///
+/// An envelope consists of
+/// 1. the envelope version (currently 1) as u8
+/// 2. the 16 byte session ID chosen randomly by the sender
+/// 3. the message counter of the session as u64
+/// 4. the time of sending in milliseconds since the unix epoch as u64
+/// 5. the serialized [Packet], encrypted with XChaCha20-Poly1305, followed by the 16 byte tag
+///
+/// All integers are little-endian. The nonce is session ID and counter, the first 33 bytes are
+/// authenticated as associated data.
+///
+/// The receiver rejects envelopes older than its maximum age and every counter it has already seen
+/// in a session (within a window of the last 64 counters), so that recorded envelopes cannot be
+/// replayed. When the receiver restarts, envelopes younger than the maximum age can be replayed
+/// once, so keep the clocks of both sites synchronized and the maximum age short.
+///
+/// Every sender starts a new random session, so multiple senders may share a key.
+/// ```rust
+/// # use coe::{Packet, TunnelReceiver, TunnelSender};
+/// let key = [7_u8; 32];
+/// let mut sender = TunnelSender::new(&key);
+/// let mut receiver = TunnelReceiver::new(&key);
+///
+/// let envelope = sender.seal(&Packet::new()).unwrap();
+/// assert_eq!(receiver.open(&envelope).unwrap(), Packet::new());
+/// // the same envelope is rejected the second time
+/// assert!(receiver.open(&envelope).is_err());
+/// ```
+pub struct TunnelSender {
+    cipher: XChaCha20Poly1305,
+    session: [u8; 16],
+    counter: u64,
+}
+impl TunnelSender {
+    /// Create a sender with the pre-shared `key`, starting a new random session.
+    pub fn new(key: &[u8; 32]) -> TunnelSender {
+        let mut session = [0_u8; 16];
+        OsRng.fill_bytes(&mut session);
+        TunnelSender {
+            cipher: XChaCha20Poly1305::new(key.into()),
+            session,
+            counter: 0,
+        }
+    }
+
+    /// Seal `packet` into an envelope, timestamped with the current time.
+    pub fn seal(&mut self, packet: &Packet) -> Result<Vec<u8>, TunnelError> {
+        self.seal_at(packet, SystemTime::now())
+    }
+
+    /// Seal `packet` into an envelope, timestamped with `now`.
+    pub fn seal_at(&mut self, packet: &Packet, now: SystemTime) -> Result<Vec<u8>, TunnelError> {
+        let counter = self.counter;
+        self.counter = counter
+            .checked_add(1)
+            .ok_or(TunnelError::CounterExhausted)?;
+        let mut header = [0_u8; HEADER_LENGTH];
+        header[0] = VERSION;
+        header[1..17].copy_from_slice(&self.session);
+        header[17..25].copy_from_slice(&counter.to_le_bytes());
+        header[25..33].copy_from_slice(&millis_since_epoch(now)?.to_le_bytes());
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce(&header[1..17], &header[17..25]),
+                AeadPayload {
+                    msg: &packet.serialize_into_vec(),
+                    aad: &header,
+                },
+            )
+            .expect("Encrypting a CoE packet never exceeds the XChaCha20 message length");
+        let mut envelope = Vec::with_capacity(HEADER_LENGTH + ciphertext.len());
+        envelope.extend_from_slice(&header);
+        envelope.extend_from_slice(&ciphertext);
+        Ok(envelope)
+    }
+
+    /// Receive plain CoE packets on `plain` and send them sealed from `sealed` to `peer`, forever.
+    ///
+    /// Datagrams that are not valid CoE packets are dropped.
+    pub fn run(
+        &mut self,
+        plain: &UdpSocket,
+        sealed: &UdpSocket,
+        peer: SocketAddr,
+    ) -> Result<(), TunnelError> {
+        let mut buf = [0_u8; 256];
+        loop {
+            let (length, _) = plain.recv_from(&mut buf)?;
+            if let Ok(packet) = Packet::try_from(&buf[..length]) {
+                sealed.send_to(&self.seal(&packet)?, peer)?;
+            };
+        }
+    }
+}
+
+/// The counters seen in a single session.
+struct ReplayWindow {
+    /// The highest counter seen.
+    highest: u64,
+    /// Bit `i` is set if `highest - i` was seen.
+    seen: u64,
+    /// The newest timestamp accepted in this session.
+    newest: u64,
+}
+impl ReplayWindow {
+    fn check(&self, counter: u64) -> Result<(), TunnelError> {
+        if counter > self.highest {
+            return Ok(());
+        };
+        let age = self.highest - counter;
+        if age >= WINDOW || self.seen & (1 << age) != 0 {
+            return Err(TunnelError::Replayed(counter));
+        };
+        Ok(())
+    }
+
+    fn mark(&mut self, counter: u64, timestamp: u64) {
+        if counter > self.highest {
+            let shift = counter - self.highest;
+            self.seen = if shift >= WINDOW {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.highest = counter;
+        };
+        self.seen |= 1 << (self.highest - counter);
+        self.newest = self.newest.max(timestamp);
+    }
+}
+
+/// Opens envelopes sealed by [TunnelSender]s with the same key and rejects replayed ones.
+///
+/// Sessions that have not sent anything for longer than the maximum age are forgotten, because
+/// all of their envelopes would be rejected as stale anyway.
+pub struct TunnelReceiver {
+    cipher: XChaCha20Poly1305,
+    max_age: Duration,
+    sessions: BTreeMap<[u8; 16], ReplayWindow>,
+    rejected: u64,
+}
+impl TunnelReceiver {
+    /// Create a receiver with the pre-shared `key` and a maximum age of 30 seconds.
+    pub fn new(key: &[u8; 32]) -> TunnelReceiver {
+        TunnelReceiver {
+            cipher: XChaCha20Poly1305::new(key.into()),
+            max_age: Duration::from_secs(30),
+            sessions: BTreeMap::new(),
+            rejected: 0,
+        }
+    }
+
+    /// Set the maximum difference between the timestamp of an envelope and the current time.
+    pub fn max_age(mut self, max_age: Duration) -> TunnelReceiver {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The number of envelopes rejected by [TunnelReceiver::run].
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    /// Open an envelope received now.
+    pub fn open(&mut self, envelope: &[u8]) -> Result<Packet, TunnelError> {
+        self.open_at(envelope, SystemTime::now())
+    }
+
+    /// Open an envelope received at `now`.
+    pub fn open_at(&mut self, envelope: &[u8], now: SystemTime) -> Result<Packet, TunnelError> {
+        if envelope.len() < HEADER_LENGTH + TAG_LENGTH {
+            return Err(TunnelError::EnvelopeTooShort(envelope.len()));
+        };
+        let (header, ciphertext) = envelope.split_at(HEADER_LENGTH);
+        if header[0] != VERSION {
+            return Err(TunnelError::VersionNotSupported(header[0]));
+        };
+        let session: [u8; 16] = header[1..17].try_into().expect("slice has length 16");
+        let counter = u64::from_le_bytes(header[17..25].try_into().expect("slice has length 8"));
+        let timestamp = u64::from_le_bytes(header[25..33].try_into().expect("slice has length 8"));
+
+        // check freshness and replay before spending time on decryption
+        let now = millis_since_epoch(now)?;
+        let max_age = u64::try_from(self.max_age.as_millis()).unwrap_or(u64::MAX);
+        if now.abs_diff(timestamp) > max_age {
+            return Err(TunnelError::Stale(timestamp));
+        };
+        self.sessions
+            .retain(|_, w| now.saturating_sub(w.newest) <= max_age);
+        if let Some(window) = self.sessions.get(&session) {
+            window.check(counter)?;
+        };
+
+        let plaintext = self
+            .cipher
+            .decrypt(
+                &nonce(&header[1..17], &header[17..25]),
+                AeadPayload {
+                    msg: ciphertext,
+                    aad: header,
+                },
+            )
+            .map_err(|_| TunnelError::AuthenticationFailed)?;
+        // only authentic envelopes may change the state
+        self.sessions
+            .entry(session)
+            .or_insert(ReplayWindow {
+                highest: counter,
+                seen: 0,
+                newest: timestamp,
+            })
+            .mark(counter, timestamp);
+        Packet::try_from(&plaintext[..]).map_err(TunnelError::InvalidPacket)
+    }
+
+    /// Receive envelopes on `sealed` and send the opened packets from `plain` to `destination`,
+    /// forever.
+    ///
+    /// Envelopes that cannot be opened are dropped and counted in [TunnelReceiver::rejected].
+    pub fn run(
+        &mut self,
+        sealed: &UdpSocket,
+        plain: &UdpSocket,
+        destination: SocketAddr,
+    ) -> Result<(), TunnelError> {
+        // the largest envelope contains a full CoE packet
+        let mut buf = [0_u8; HEADER_LENGTH + 256 + TAG_LENGTH];
+        loop {
+            let (length, _) = sealed.recv_from(&mut buf)?;
+            match self.open(&buf[..length]) {
+                Ok(packet) => {
+                    plain.send_to(&packet.serialize_into_vec(), destination)?;
+                }
+                Err(_) => self.rejected += 1,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, Payload};
+
+    fn packet() -> Packet {
+        Packet::try_from_payloads(&[Payload::new(
+            58,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::Watt(10)),
+        )])
+        .unwrap()
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn replay_window() {
+        let key = [1_u8; 32];
+        let mut sender = TunnelSender::new(&key);
+        let mut receiver = TunnelReceiver::new(&key);
+        let envelopes = (0..100)
+            .map(|_| sender.seal_at(&packet(), at(1000)).unwrap())
+            .collect::<Vec<_>>();
+
+        // out of order within the window is fine
+        assert_eq!(
+            receiver.open_at(&envelopes[50], at(1000)).unwrap(),
+            packet()
+        );
+        assert!(receiver.open_at(&envelopes[40], at(1000)).is_ok());
+        assert!(matches!(
+            receiver.open_at(&envelopes[40], at(1000)),
+            Err(TunnelError::Replayed(40))
+        ));
+        assert!(matches!(
+            receiver.open_at(&envelopes[50], at(1000)),
+            Err(TunnelError::Replayed(50))
+        ));
+        assert!(receiver.open_at(&envelopes[99], at(1000)).is_ok());
+        // too far behind the highest counter
+        assert!(matches!(
+            receiver.open_at(&envelopes[35], at(1000)),
+            Err(TunnelError::Replayed(35))
+        ));
+        assert!(receiver.open_at(&envelopes[36], at(1000)).is_ok());
+
+        // a second sender has its own session
+        let mut other = TunnelSender::new(&key);
+        assert!(receiver
+            .open_at(&other.seal_at(&packet(), at(1000)).unwrap(), at(1000))
+            .is_ok());
+    }
+
+    #[test]
+    fn stale() {
+        let key = [1_u8; 32];
+        let mut sender = TunnelSender::new(&key);
+        let mut receiver = TunnelReceiver::new(&key).max_age(Duration::from_secs(10));
+        let envelope = sender.seal_at(&packet(), at(1000)).unwrap();
+        assert!(matches!(
+            receiver.open_at(&envelope, at(1011)),
+            Err(TunnelError::Stale(1_000_000))
+        ));
+        assert!(matches!(
+            receiver.open_at(&envelope, at(989)),
+            Err(TunnelError::Stale(1_000_000))
+        ));
+        assert!(receiver.open_at(&envelope, at(1010)).is_ok());
+    }
+
+    #[test]
+    fn tampered() {
+        let mut sender = TunnelSender::new(&[1_u8; 32]);
+        let mut receiver = TunnelReceiver::new(&[1_u8; 32]);
+        let envelope = sender.seal_at(&packet(), at(1000)).unwrap();
+
+        for i in 0..envelope.len() {
+            let mut modified = envelope.clone();
+            modified[i] ^= 1;
+            assert!(receiver.open_at(&modified, at(1000)).is_err(), "byte {i}");
+        }
+        assert!(matches!(
+            receiver.open_at(&envelope[..48], at(1000)),
+            Err(TunnelError::EnvelopeTooShort(48))
+        ));
+        assert!(matches!(
+            TunnelReceiver::new(&[2_u8; 32]).open_at(&envelope, at(1000)),
+            Err(TunnelError::AuthenticationFailed)
+        ));
+        // the modifications did not poison the session
+        assert!(receiver.open_at(&envelope, at(1000)).is_ok());
+    }
+
+    #[test]
+    fn tunnel_over_localhost() {
+        let key = [3_u8; 32];
+        let source = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let site_a_plain = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let site_a_sealed = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let site_b_sealed = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let site_b_plain = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let cmi = UdpSocket::bind("127.0.0.1:0").unwrap();
+        cmi.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let site_a = site_a_plain.local_addr().unwrap();
+        let site_b = site_b_plain.local_addr().unwrap();
+        let peer = site_b_sealed.local_addr().unwrap();
+        let destination = cmi.local_addr().unwrap();
+        std::thread::spawn(move || {
+            TunnelSender::new(&key).run(&site_a_plain, &site_a_sealed, peer)
+        });
+        std::thread::spawn(move || {
+            TunnelReceiver::new(&key).run(&site_b_sealed, &site_b_plain, destination)
+        });
+
+        source.send_to(&[1, 2, 3], site_a).unwrap();
+        source
+            .send_to(&packet().serialize_into_vec(), site_a)
+            .unwrap();
+        let mut buf = [0_u8; 256];
+        let (length, from) = cmi.recv_from(&mut buf).unwrap();
+        assert_eq!(Packet::try_from(&buf[..length]), Ok(packet()));
+        assert_eq!(from, site_b);
+    }
+}
@@ -0,0 +1,335 @@
+//! Conversion between analogue units of the same physical dimension.
+//!
+//! Every convertible unit is described by how its raw on-wire value relates to a base unit of its
+//! [Dimension]: `base = raw * factor + offset`. Base units are chosen small enough for all factors
+//! to be integers, so that conversions only round once, when dividing by the factor of the target
+//! unit.
+//!
+//! Independent of the dimension, every analogue unit has a number of decimal places and a symbol,
+//! which TA-Hardware uses to show the raw value.
+
+use crate::{date::DATE_UNIT_ID, AnalogueCOEValue, ParseCOEError};
+
+/// The physical dimension of an analogue unit.
+///
+/// Values can be converted between all units of the same dimension with
+/// [AnalogueCOEValue::try_convert_to].
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Dimension {
+    /// °C and K
+    ///
+    /// Base unit: 1/100 K
+    Temperature,
+    /// bar, mbar and Pa
+    ///
+    /// Base unit: Pa
+    Pressure,
+    /// W and kW
+    ///
+    /// Base unit: W
+    Power,
+    /// kWh and MWh
+    ///
+    /// Base unit: Wh
+    Energy,
+    /// l/h, l/min, l/d, m³/min, m³/h and m³/d
+    ///
+    /// Base unit: l/d
+    VolumeFlow,
+    /// l and m³
+    ///
+    /// Base unit: l
+    Volume,
+    /// km, m, cm and mm
+    ///
+    /// Base unit: mm
+    Length,
+    /// t, kg and g
+    ///
+    /// Base unit: 1/10 g
+    Mass,
+    /// s, min, h and d (but not the time of day)
+    ///
+    /// Base unit: 1/10 s
+    Duration,
+    /// km/h and m/s
+    ///
+    /// Base unit: m/h
+    Velocity,
+    /// mm/min, mm/h and mm/d
+    ///
+    /// Base unit: 1/10 mm/d
+    PrecipitationRate,
+    /// mA and A
+    ///
+    /// Base unit: 1/10 mA
+    ElectricCurrent,
+    /// ° in different precisions
+    ///
+    /// Base unit: 1/1_000_000 °
+    Angle,
+    /// Dimensionless values in different precisions
+    ///
+    /// Base unit: 1/10
+    Dimensionless,
+    /// l/Imp. and m³/Imp.
+    ///
+    /// Base unit: 1/100_000 l
+    VolumePerPulse,
+}
+impl Dimension {
+    /// Get the dimension of the unit with ID `unit_id`.
+    ///
+    /// Returns `None` for units that cannot be converted into any other unit.
+    /// ```rust
+    /// # use coe::Dimension;
+    /// assert_eq!(Dimension::of_unit(1), Some(Dimension::Temperature));
+    /// assert_eq!(Dimension::of_unit(60), None);
+    /// ```
+    pub fn of_unit(unit_id: u8) -> Option<Dimension> {
+        AnalogueCOEValue::scale(unit_id).map(|(dimension, _, _)| dimension)
+    }
+}
+impl Dimension {
+    /// The snake_case name of this dimension, as used by [Display](core::fmt::Display).
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Pressure => "pressure",
+            Self::Power => "power",
+            Self::Energy => "energy",
+            Self::VolumeFlow => "volume_flow",
+            Self::Volume => "volume",
+            Self::Length => "length",
+            Self::Mass => "mass",
+            Self::Duration => "duration",
+            Self::Velocity => "velocity",
+            Self::PrecipitationRate => "precipitation_rate",
+            Self::ElectricCurrent => "electric_current",
+            Self::Angle => "angle",
+            Self::Dimensionless => "dimensionless",
+            Self::VolumePerPulse => "volume_per_pulse",
+        }
+    }
+}
+impl core::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The Errors that can occur when converting an [AnalogueCOEValue] into another unit.
+#[derive(Hash, Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConvertUnitError {
+    /// The unit with this ID has no [Dimension] and cannot be converted.
+    NotConvertible(u8),
+    /// The units (from, to) have different dimensions.
+    DimensionMismatch(u8, u8),
+    /// The converted value does not fit into the target unit with this ID.
+    Overflow(u8),
+}
+impl core::fmt::Display for ConvertUnitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::NotConvertible(x) => {
+                write!(f, "The unit with ID {x} cannot be converted.")
+            }
+            Self::DimensionMismatch(from, to) => {
+                write!(
+                    f,
+                    "The units with ID {from} and {to} have different dimensions."
+                )
+            }
+            Self::Overflow(x) => {
+                write!(f, "The value does not fit into the unit with ID {x}.")
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for ConvertUnitError {}
+
+/// `10^decimals` for all decimals used by a unit.
+const POWERS_OF_TEN: [f64; 7] = [1.0, 10.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+
+/// Round `value` half away from zero, if the result fits into an i32.
+// f64::round is not available in core, so we round by hand.
+fn round_to_i32(value: f64) -> Option<i32> {
+    // NaN fails both comparisons
+    if !(value > f64::from(i32::MIN) - 0.5 && value < f64::from(i32::MAX) + 0.5) {
+        return None;
+    };
+    // The `as` casts truncate towards zero, which cannot overflow after the check above.
+    let rounded = if value >= 0.0 {
+        (value + 0.5) as i64
+    } else {
+        (value - 0.5) as i64
+    };
+    i32::try_from(rounded).ok()
+}
+
+/// Divide, rounding half away from zero.
+fn div_round(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if 2 * remainder.abs() >= denominator.abs() {
+        quotient + numerator.signum() * denominator.signum()
+    } else {
+        quotient
+    }
+}
+
+impl AnalogueCOEValue {
+    /// Create an [AnalogueCOEValue] from a unit ID and its raw on-wire value.
+    ///
+    /// ```rust
+    /// # use coe::AnalogueCOEValue;
+    /// let val = AnalogueCOEValue::from_raw(1, 215);
+    /// assert_eq!(val, Ok(AnalogueCOEValue::DegreeCentigrade_Tens(215)));
+    /// ```
+    pub fn from_raw(unit_id: u8, raw: i32) -> Result<AnalogueCOEValue, ParseCOEError> {
+        (&unit_id, &raw.to_le_bytes()[..]).try_into()
+    }
+
+    /// The raw on-wire value, without any scaling applied.
+    ///
+    /// For [AnalogueCOEValue::Date], this is the packed little-endian representation.
+    /// ```rust
+    /// # use coe::AnalogueCOEValue;
+    /// assert_eq!(AnalogueCOEValue::DegreeCentigrade_Tens(215).raw(), 215);
+    /// ```
+    pub const fn raw(&self) -> i32 {
+        i32::from_le_bytes(self.value_bytes())
+    }
+
+    /// The number of decimal places of this unit.
+    ///
+    /// The value shown by TA-Hardware is the raw value divided by `10^decimals`.
+    /// ```rust
+    /// # use coe::AnalogueCOEValue;
+    /// assert_eq!(AnalogueCOEValue::DegreeCentigrade_Tens(215).decimals(), 1);
+    /// assert_eq!(AnalogueCOEValue::Watt(215).decimals(), 0);
+    /// ```
+    pub fn decimals(&self) -> u8 {
+        Self::meta(self.unit_id())
+            .expect("Every analogue unit has metadata")
+            .0
+    }
+
+    /// The symbol of this unit, e.g. `°C` or `kWh`.
+    ///
+    /// This is empty for units without a physical unit.
+    /// ```rust
+    /// # use coe::AnalogueCOEValue;
+    /// assert_eq!(AnalogueCOEValue::DegreeCentigrade_Tens(215).unit_symbol(), "°C");
+    /// assert_eq!(AnalogueCOEValue::Dimensionless(215).unit_symbol(), "");
+    /// ```
+    pub fn unit_symbol(&self) -> &'static str {
+        Self::meta(self.unit_id())
+            .expect("Every analogue unit has metadata")
+            .1
+    }
+
+    /// The value as shown by TA-Hardware, i.e. the raw value scaled by [Self::decimals].
+    ///
+    /// Returns `None` for [AnalogueCOEValue::Date], which is not a number.
+    /// ```rust
+    /// # use coe::AnalogueCOEValue;
+    /// assert_eq!(AnalogueCOEValue::DegreeCentigrade_Tens(215).scaled(), Some(21.5));
+    /// assert_eq!(AnalogueCOEValue::Date(17, 6, 2024).scaled(), None);
+    /// ```
+    pub fn scaled(&self) -> Option<f64> {
+        if let AnalogueCOEValue::Date(_, _, _) = self {
+            return None;
+        };
+        Some(f64::from(self.raw()) / POWERS_OF_TEN[usize::from(self.decimals())])
+    }
+
+    /// Create a value from a unit ID and the value as shown by TA-Hardware.
+    ///
+    /// The value is rounded half away from zero to the precision of the unit.
+    /// Fails for unknown units and for [AnalogueCOEValue::Date].
+    /// ```rust
+    /// # use coe::{AnalogueCOEValue, ConvertUnitError};
+    /// let val = AnalogueCOEValue::from_scaled(1, 21.54);
+    /// assert_eq!(val, Ok(AnalogueCOEValue::DegreeCentigrade_Tens(215)));
+    ///
+    /// let val = AnalogueCOEValue::from_scaled(62, 1.0);
+    /// assert_eq!(val, Err(ConvertUnitError::NotConvertible(62)));
+    /// ```
+    pub fn from_scaled(unit_id: u8, value: f64) -> Result<AnalogueCOEValue, ConvertUnitError> {
+        let (decimals, _) = Self::meta(unit_id).ok_or(ConvertUnitError::NotConvertible(unit_id))?;
+        if unit_id == DATE_UNIT_ID {
+            return Err(ConvertUnitError::NotConvertible(unit_id));
+        };
+        let raw = round_to_i32(value * POWERS_OF_TEN[usize::from(decimals)])
+            .ok_or(ConvertUnitError::Overflow(unit_id))?;
+        Ok(AnalogueCOEValue::from_raw(unit_id, raw)
+            .expect("All units with metadata except Date are analogue units with a 4-byte value"))
+    }
+
+    /// The [Dimension] of this value, or `None` if it cannot be converted into another unit.
+    pub fn dimension(&self) -> Option<Dimension> {
+        Dimension::of_unit(self.unit_id())
+    }
+
+    /// Convert this value into the unit with ID `unit_id`.
+    ///
+    /// Both units must have the same [Dimension]. The result is rounded half away from zero to
+    /// the precision of the target unit.
+    /// ```rust
+    /// # use coe::{AnalogueCOEValue, ConvertUnitError};
+    /// // 294.7 K == 21.55 °C, which is rounded to 21.6 °C
+    /// let val = AnalogueCOEValue::DegreeKelvin_Tens(2947).try_convert_to(1);
+    /// assert_eq!(val, Ok(AnalogueCOEValue::DegreeCentigrade_Tens(216)));
+    ///
+    /// // 1234.5 mbar == 1.23 bar
+    /// let val = AnalogueCOEValue::Millibar_Tens(12345).try_convert_to(23);
+    /// assert_eq!(val, Ok(AnalogueCOEValue::Bar_Hundreds(123)));
+    ///
+    /// let val = AnalogueCOEValue::Millibar_Tens(12345).try_convert_to(1);
+    /// assert_eq!(val, Err(ConvertUnitError::DimensionMismatch(65, 1)));
+    ///
+    /// let val = AnalogueCOEValue::MegawattHour(i32::MAX).try_convert_to(11);
+    /// assert_eq!(val, Err(ConvertUnitError::Overflow(11)));
+    /// ```
+    pub fn try_convert_to(&self, unit_id: u8) -> Result<AnalogueCOEValue, ConvertUnitError> {
+        let (from_dimension, base) =
+            to_base(self).ok_or(ConvertUnitError::NotConvertible(self.unit_id()))?;
+        let (to_dimension, to_factor, to_offset) =
+            AnalogueCOEValue::scale(unit_id).ok_or(ConvertUnitError::NotConvertible(unit_id))?;
+        if from_dimension != to_dimension {
+            return Err(ConvertUnitError::DimensionMismatch(self.unit_id(), unit_id));
+        };
+        let raw = div_round(base - i128::from(to_offset), i128::from(to_factor));
+        let raw = i32::try_from(raw).map_err(|_| ConvertUnitError::Overflow(unit_id))?;
+        Ok(AnalogueCOEValue::from_raw(unit_id, raw)
+            .expect("All units with a dimension are analogue units with a 4-byte value"))
+    }
+}
+
+/// Get the [Dimension] of `value` and its value in the base unit of that dimension.
+pub(crate) fn to_base(value: &AnalogueCOEValue) -> Option<(Dimension, i128)> {
+    let (dimension, factor, offset) = AnalogueCOEValue::scale(value.unit_id())?;
+    Some((
+        dimension,
+        i128::from(value.raw()) * i128::from(factor) + i128::from(offset),
+    ))
+}
+
+/// Create a value in the unit with ID `unit_id` from a value in the base unit of its dimension.
+///
+/// The result is rounded half away from zero to the precision of the unit.
+#[cfg(feature = "uom")]
+pub(crate) fn from_base_f64(unit_id: u8, base: f64) -> Result<AnalogueCOEValue, ConvertUnitError> {
+    let (_, factor, offset) =
+        AnalogueCOEValue::scale(unit_id).ok_or(ConvertUnitError::NotConvertible(unit_id))?;
+    let raw = round_to_i32((base - offset as f64) / factor as f64)
+        .ok_or(ConvertUnitError::Overflow(unit_id))?;
+    Ok(AnalogueCOEValue::from_raw(unit_id, raw)
+        .expect("All units with a dimension are analogue units with a 4-byte value"))
+}
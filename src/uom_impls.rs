@@ -0,0 +1,172 @@
+//! Conversions between analogue values and the quantities of [uom].
+//!
+//! Every [Dimension] with a matching [uom] quantity can be converted from any unit of that
+//! dimension into the quantity. Converting a quantity into an [AnalogueCOEValue] chooses the most
+//! precise unit CoE offers for that dimension and rounds to its precision. Use
+//! [AnalogueCOEValue::try_convert_to] afterwards if the receiver expects a different unit.
+
+use ::uom::si::{
+    angle::degree,
+    electric_current::milliampere,
+    energy::watt_hour,
+    f64::{
+        Angle, ElectricCurrent, Energy, Length, Mass, Power, Pressure, ThermodynamicTemperature,
+        Time, Velocity, Volume, VolumeRate,
+    },
+    length::millimeter,
+    mass::gram,
+    power::watt,
+    pressure::pascal,
+    thermodynamic_temperature::kelvin,
+    time::second,
+    velocity::meter_per_second,
+    volume::liter,
+    volume_rate::liter_per_second,
+};
+
+use crate::{units, AnalogueCOEValue, ConvertUnitError, Dimension};
+
+/// Implement the conversions for one quantity.
+///
+/// - `$quantity`: the [uom] quantity
+/// - `$dimension`: the [Dimension] matching `$quantity`
+/// - `$unit`: a unit of `$quantity`
+/// - `$base_per_unit`: how many base units of `$dimension` (see [units]) make up one `$unit`
+/// - `$unit_id`: the unit to create when converting from `$quantity`
+macro_rules! impl_quantity {
+    ($quantity:ident, $dimension:ident, $unit:ident, $base_per_unit:expr, $unit_id:literal) => {
+        #[doc = concat!("Convert an analogue value into a [", stringify!($quantity), "].")]
+        ///
+        #[doc = concat!("Fails if the value is not of [Dimension::", stringify!($dimension), "].")]
+        impl TryFrom<AnalogueCOEValue> for $quantity {
+            type Error = ConvertUnitError;
+            fn try_from(value: AnalogueCOEValue) -> Result<Self, Self::Error> {
+                match units::to_base(&value) {
+                    Some((Dimension::$dimension, base)) => {
+                        Ok($quantity::new::<$unit>(base as f64 / $base_per_unit))
+                    }
+                    Some(_) => Err(ConvertUnitError::DimensionMismatch(
+                        value.unit_id(),
+                        $unit_id,
+                    )),
+                    None => Err(ConvertUnitError::NotConvertible(value.unit_id())),
+                }
+            }
+        }
+
+        #[doc = concat!("Convert a [", stringify!($quantity), "] into an analogue value with unit ID ", stringify!($unit_id), ".")]
+        ///
+        /// Fails if the value does not fit into the unit.
+        impl TryFrom<$quantity> for AnalogueCOEValue {
+            type Error = ConvertUnitError;
+            fn try_from(value: $quantity) -> Result<Self, Self::Error> {
+                units::from_base_f64($unit_id, value.get::<$unit>() * $base_per_unit)
+            }
+        }
+    };
+}
+
+// DegreeCentigrade_Tens
+impl_quantity!(ThermodynamicTemperature, Temperature, kelvin, 100.0, 1);
+// Pascal
+impl_quantity!(Pressure, Pressure, pascal, 1.0, 66);
+// Watt
+impl_quantity!(Power, Power, watt, 1.0, 69);
+// KilowattHour_Tens
+impl_quantity!(Energy, Energy, watt_hour, 1.0, 11);
+// LiterPerHour
+impl_quantity!(VolumeRate, VolumeFlow, liter_per_second, 86_400.0, 3);
+// Liters
+impl_quantity!(Volume, Volume, liter, 1.0, 19);
+// MilliMeter
+impl_quantity!(Length, Length, millimeter, 1.0, 27);
+// Gram_Tens
+impl_quantity!(Mass, Mass, gram, 10.0, 72);
+// Second_Tens
+impl_quantity!(Time, Duration, second, 10.0, 57);
+// KiloMetersPerHour
+impl_quantity!(Velocity, Velocity, meter_per_second, 3_600.0, 20);
+// MilliAmpere_Tens
+impl_quantity!(ElectricCurrent, ElectricCurrent, milliampere, 10.0, 14);
+// Degree_Millions
+impl_quantity!(Angle, Angle, degree, 1_000_000.0, 56);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::uom::si::{
+        power::kilowatt, pressure::bar, thermodynamic_temperature::degree_celsius,
+        volume_rate::cubic_meter_per_hour,
+    };
+
+    #[test]
+    fn temperature() {
+        let t = ThermodynamicTemperature::try_from(AnalogueCOEValue::DegreeCentigrade_Tens(215))
+            .unwrap();
+        assert!((t.get::<degree_celsius>() - 21.5).abs() < 1e-9);
+        let t = ThermodynamicTemperature::new::<degree_celsius>(21.54);
+        assert_eq!(
+            AnalogueCOEValue::try_from(t),
+            Ok(AnalogueCOEValue::DegreeCentigrade_Tens(215))
+        );
+        let t = ThermodynamicTemperature::new::<kelvin>(0.0);
+        assert_eq!(
+            AnalogueCOEValue::try_from(t),
+            Ok(AnalogueCOEValue::DegreeCentigrade_Tens(-2732))
+        );
+    }
+
+    #[test]
+    fn pressure() {
+        let p = Pressure::try_from(AnalogueCOEValue::Bar_Hundreds(150)).unwrap();
+        assert!((p.get::<bar>() - 1.5).abs() < 1e-9);
+        assert_eq!(
+            AnalogueCOEValue::try_from(p),
+            Ok(AnalogueCOEValue::Pascal(150_000))
+        );
+    }
+
+    #[test]
+    fn power() {
+        let p = Power::try_from(AnalogueCOEValue::KiloWatt_Hundreds(1234)).unwrap();
+        assert!((p.get::<kilowatt>() - 12.34).abs() < 1e-9);
+        assert_eq!(
+            AnalogueCOEValue::try_from(p),
+            Ok(AnalogueCOEValue::Watt(12_340))
+        );
+    }
+
+    #[test]
+    fn volume_rate() {
+        let v = VolumeRate::try_from(AnalogueCOEValue::CubicMeterPerHour(3)).unwrap();
+        assert!((v.get::<cubic_meter_per_hour>() - 3.0).abs() < 1e-9);
+        assert_eq!(
+            AnalogueCOEValue::try_from(v),
+            Ok(AnalogueCOEValue::LiterPerHour(3000))
+        );
+    }
+
+    #[test]
+    fn wrong_dimension() {
+        assert_eq!(
+            Power::try_from(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+            Err(ConvertUnitError::DimensionMismatch(1, 69))
+        );
+        assert_eq!(
+            Power::try_from(AnalogueCOEValue::Time(215)),
+            Err(ConvertUnitError::NotConvertible(60))
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            AnalogueCOEValue::try_from(Power::new::<kilowatt>(1e10)),
+            Err(ConvertUnitError::Overflow(69))
+        );
+        assert_eq!(
+            AnalogueCOEValue::try_from(Power::new::<watt>(f64::NAN)),
+            Err(ConvertUnitError::Overflow(69))
+        );
+    }
+}
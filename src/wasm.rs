@@ -0,0 +1,192 @@
+//! wasm-bindgen exports for decoding and encoding CoE in the browser.
+//!
+//! Payloads are exchanged as plain JS objects in the [Readable] representation:
+//! `{ node, pdo, unit_id, unit, value }`, where `value` is the scaled number for analogue values,
+//! a boolean for digital values and a `YYYY-MM-DD` string for dates.
+//!
+//! Build the module with
+//! ```sh
+//! cargo rustc --release --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/coe.wasm
+//! ```
+
+use std::{
+    fmt::Write,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{CaptureError, CaptureReader, Packet, Payload, Readable};
+
+/// Decode a CoE packet into an array of payloads.
+#[wasm_bindgen(js_name = decodePacket)]
+pub fn decode_packet(bytes: &[u8]) -> Result<JsValue, JsError> {
+    let packet = Packet::try_from(bytes)?;
+    Ok(serde_wasm_bindgen::to_value(&Readable(packet))?)
+}
+
+/// Decode a hex dump of a CoE packet into an array of payloads.
+///
+/// Whitespace, `:`, `-` and `,` between the bytes and `0x` prefixes are ignored.
+#[wasm_bindgen(js_name = decodeHex)]
+pub fn decode_hex(hex: &str) -> Result<JsValue, JsError> {
+    decode_packet(&parse_hex(hex).map_err(|e| JsError::new(&e))?)
+}
+
+/// Decode a capture file recorded with `coe record` into an array of
+/// `{ timestamp, source, localAddress, data, payloads, error }`.
+///
+/// `timestamp` is in milliseconds since the unix epoch and `data` is the hex dump of the
+/// datagram. Exactly one of `payloads` and `error` is set, depending on whether the datagram was a
+/// valid CoE packet.
+#[wasm_bindgen(js_name = decodeCapture)]
+pub fn decode_capture(bytes: &[u8]) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(&captured_datagrams(bytes)?)?)
+}
+
+/// Validate a single payload object and return it normalized, i.e. with the unit symbol set and
+/// the value rounded to the precision of its unit.
+#[wasm_bindgen(js_name = makePayload)]
+pub fn make_payload(payload: JsValue) -> Result<JsValue, JsError> {
+    let payload: Readable<Payload> = serde_wasm_bindgen::from_value(payload)?;
+    Ok(serde_wasm_bindgen::to_value(&payload)?)
+}
+
+/// Encode an array of at most 31 payload objects into a CoE packet.
+#[wasm_bindgen(js_name = encodePacket)]
+pub fn encode_packet(payloads: JsValue) -> Result<Vec<u8>, JsError> {
+    let packet: Readable<Packet> = serde_wasm_bindgen::from_value(payloads)?;
+    Ok(packet.0.serialize_into_vec())
+}
+
+/// Parse a hex dump into bytes.
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let mut digits = Vec::new();
+    for token in hex.split(|c: char| c.is_whitespace() || matches!(c, ':' | '-' | ',')) {
+        let token = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+            .unwrap_or(token);
+        for c in token.chars() {
+            digits.push(
+                c.to_digit(16)
+                    .ok_or_else(|| format!("'{c}' is not a hex digit"))? as u8,
+            );
+        }
+    }
+    if digits.len() % 2 != 0 {
+        return Err(format!(
+            "{} hex digits cannot form whole bytes",
+            digits.len()
+        ));
+    };
+    Ok(digits.chunks(2).map(|x| x[0] << 4 | x[1]).collect())
+}
+
+/// A datagram of a capture file, as returned by [decode_capture].
+#[derive(Serialize)]
+struct CapturedDatagram {
+    timestamp: f64,
+    source: String,
+    #[serde(rename = "localAddress")]
+    local_address: String,
+    data: String,
+    payloads: Option<Readable<Packet>>,
+    error: Option<String>,
+}
+
+fn captured_datagrams(bytes: &[u8]) -> Result<Vec<CapturedDatagram>, CaptureError> {
+    CaptureReader::new(bytes)?
+        .map(|record| {
+            let record = record?;
+            let mut data = String::with_capacity(record.data.len() * 2);
+            for byte in &record.data {
+                write!(data, "{byte:02x}").expect("Writing to a String does not fail");
+            }
+            let (payloads, error) = match record.packet() {
+                Ok(packet) => (Some(Readable(packet)), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            Ok(CapturedDatagram {
+                timestamp: record
+                    .timestamp
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    * 1000.0,
+                source: record.source.to_string(),
+                local_address: record.local_address.to_string(),
+                data,
+                payloads,
+                error,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnalogueCOEValue, COEValue, CaptureRecord, CaptureWriter};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_hex("02 00 04 00"), Ok(Vec::from([2, 0, 4, 0])));
+        assert_eq!(
+            parse_hex("0x02,0x00\n0xff:Ab-"),
+            Ok(Vec::from([2, 0, 255, 171]))
+        );
+        assert_eq!(parse_hex(""), Ok(Vec::new()));
+        assert!(parse_hex("020").is_err());
+        assert!(parse_hex("0g").is_err());
+    }
+
+    #[test]
+    fn capture() {
+        let packet = Packet::try_from_payloads(&[Payload::new(
+            58,
+            1,
+            COEValue::Analogue(AnalogueCOEValue::DegreeCentigrade_Tens(215)),
+        )])
+        .unwrap();
+        let mut writer = CaptureWriter::new(Vec::new()).unwrap();
+        for data in [packet.serialize_into_vec(), Vec::from([2, 0])] {
+            writer
+                .write_record(&CaptureRecord {
+                    timestamp: UNIX_EPOCH + Duration::from_millis(1500),
+                    source: "192.168.1.2:5442".parse().unwrap(),
+                    local_address: "192.168.1.3:5441".parse().unwrap(),
+                    data,
+                })
+                .unwrap();
+        }
+        let datagrams = captured_datagrams(&writer.into_inner()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&datagrams).unwrap(),
+            serde_json::json!([
+                {
+                    "timestamp": 1500.0,
+                    "source": "192.168.1.2:5442",
+                    "localAddress": "192.168.1.3:5441",
+                    "data": "02000c013a010101d7000000",
+                    "payloads": [
+                        {"node": 58, "pdo": 1, "unit_id": 1, "unit": "°C", "value": 21.5}
+                    ],
+                    "error": null,
+                },
+                {
+                    "timestamp": 1500.0,
+                    "source": "192.168.1.2:5442",
+                    "localAddress": "192.168.1.3:5441",
+                    "data": "0200",
+                    "payloads": null,
+                    "error": "The packet is not at least 4 byte long.",
+                },
+            ])
+        );
+    }
+}